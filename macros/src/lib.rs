@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, FnArg, Ident, ImplItemMethod, Pat, PathArguments, Type};
+
+/// Tracks storage usage changes made by the contract method and charges them to an account
+///
+/// The account is taken from the named method argument of `AccountId` or `Option<AccountId>` type,
+/// falling back to the predecessor if the argument is omitted or `None`:
+///
+/// ```ignore
+/// #[near_bindgen]
+/// impl Contract {
+///     #[storage_tracked(account_id)]
+///     pub fn set_data(&mut self, account_id: Option<AccountId>, data: String) { ... }
+///
+///     #[storage_tracked]
+///     pub fn set_own_data(&mut self, data: String) { ... }
+/// }
+/// ```
+///
+/// Expands to `Contract::start_storage_tracking` and `Contract::finish_storage_tracking` calls
+/// around the method body, which flush contract collections and panic if the account
+/// storage balance doesn't cover its storage usage.
+#[proc_macro_attribute]
+pub fn storage_tracked(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let account_arg = if attr.is_empty() {
+        None
+    } else {
+        Some(parse_macro_input!(attr as Ident))
+    };
+    let method = parse_macro_input!(item as ImplItemMethod);
+
+    match expand(account_arg, method) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(
+    account_arg: Option<Ident>,
+    method: ImplItemMethod,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let ImplItemMethod {
+        attrs,
+        vis,
+        defaultness,
+        sig,
+        block,
+    } = method;
+
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver)) if receiver.mutability.is_some() => {}
+        _ => {
+            return Err(syn::Error::new(
+                sig.span(),
+                "#[storage_tracked] requires `&mut self` receiver",
+            ))
+        }
+    }
+
+    let account_id = match account_arg {
+        Some(account_arg) => {
+            let ty = find_arg_type(&sig.inputs, &account_arg)?;
+
+            if is_option(ty) {
+                quote! {
+                    ::core::clone::Clone::clone(&#account_arg)
+                        .unwrap_or_else(::near_sdk::env::predecessor_account_id)
+                }
+            } else {
+                quote! { ::core::clone::Clone::clone(&#account_arg) }
+            }
+        }
+        None => quote! { ::near_sdk::env::predecessor_account_id() },
+    };
+
+    let output = &sig.output;
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #defaultness #sig {
+            let __storage_tracked_account_id: ::near_sdk::AccountId = #account_id;
+            let __storage_tracker = self.start_storage_tracking(&__storage_tracked_account_id);
+            #[allow(clippy::redundant_closure_call)]
+            let __storage_tracked_result = (|| #output #block)();
+            self.finish_storage_tracking(&__storage_tracked_account_id, __storage_tracker);
+            __storage_tracked_result
+        }
+    })
+}
+
+/// Returns type of the method argument named `name`
+fn find_arg_type<'a>(
+    inputs: impl IntoIterator<Item = &'a FnArg>,
+    name: &Ident,
+) -> syn::Result<&'a Type> {
+    inputs
+        .into_iter()
+        .find_map(|arg| match arg {
+            FnArg::Typed(arg) => match arg.pat.as_ref() {
+                Pat::Ident(pat) if &pat.ident == name => Some(arg.ty.as_ref()),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .ok_or_else(|| syn::Error::new(name.span(), format!("argument `{}` not found", name)))
+}
+
+/// Checks if type is `Option<T>`
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .map(|segment| {
+                segment.ident == "Option"
+                    && matches!(segment.arguments, PathArguments::AngleBracketed(_))
+            })
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
@@ -1,27 +1,66 @@
 use crate::misc::Hash;
-use crate::storage::{MAX_ACCOUNT_ID_LENGTH, REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE};
+use crate::storage::{
+    ACCOUNT_IDS_MAX_ID_LENGTH_STORAGE, ACCOUNT_ID_ENTRIES, ACCOUNT_STORAGE, ACCOUNT_V1_STORAGE,
+    MAX_ACCOUNT_ID_LENGTH, REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE, U128_STORAGE,
+    U32_STORAGE,
+};
 use near_contract_standards::storage_management::StorageBalance;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U128;
-use near_sdk::{env, AccountId, Balance, StorageUsage};
+use near_sdk::{env, require, AccountId, Balance, StorageUsage, Timestamp};
 
 /// Current account data struct
 ///
 /// Contains most recent storage data
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub(crate) struct Account {
     /// Total account storage deposit amount in $NEAR
     pub(crate) storage_balance: u128,
     /// Total used by account storage amount in bytes
     pub(crate) storage_usage: StorageUsage,
+    /// Account registration timestamp in nanoseconds, zero if registered before it was tracked
+    pub(crate) registered_at: Timestamp,
+    /// Account which sponsored the account storage
+    pub(crate) sponsor_id: Option<AccountId>,
+    /// Part of storage balance deposited by sponsor, which isn't withdrawable by the account
+    pub(crate) sponsored_balance: u128,
+    /// Whether account is frozen by the owner, frozen account can't deposit or withdraw
+    pub(crate) frozen: bool,
+    /// Fungible token balance, always zero unless the contract is built with `ft` feature
+    pub(crate) ft_balance: Balance,
+    /// Storage balance credit funded in a whitelisted fungible token
+    pub(crate) token_credit: Option<TokenCredit>,
+}
+
+/// Part of account storage balance funded in a whitelisted fungible token via `ft_on_transfer`
+///
+/// Credited balance isn't withdrawable in $NEAR, the deposited tokens are returned instead
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TokenCredit {
+    /// Fungible token contract, which tokens were deposited
+    pub(crate) token_id: AccountId,
+    /// Deposited amount of tokens
+    pub(crate) amount: Balance,
+    /// Part of storage balance in $NEAR credited for the deposited tokens
+    pub(crate) storage_balance: Balance,
+}
+
+/// Legacy account data struct of the first release
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub(crate) struct AccountV1 {
+    /// Total account storage deposit amount in $NEAR
+    pub(crate) storage_balance: u128,
+    /// Total used by account storage amount in bytes
+    pub(crate) storage_usage: StorageUsage,
 }
 
 /// Versioned account data struct
 ///
 /// Could contain legacy account data structs,
 /// which would be upgraded to current version upon next write access
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
 pub(crate) enum VAccount {
+    V1(AccountV1),
     Current(Account),
 }
 
@@ -31,17 +70,31 @@ impl Account {
         Self {
             storage_balance: storage_balance.unwrap_or_default(),
             storage_usage: Self::initial_storage_usage(Some(account_id)),
+            registered_at: env::block_timestamp(),
+            sponsor_id: None,
+            sponsored_balance: 0,
+            frozen: false,
+            ft_balance: 0,
+            token_credit: None,
         }
     }
 
     /// Returns storage usage by optionally provided `account_id`, otherwise use maximum account id length
     pub(crate) fn initial_storage_usage(account_id: Option<&AccountId>) -> u64 {
-        // compute storage usage diff for specific `account id` length, which is used when saving account data in lookup map
+        // compute storage usage diff for specific `account id` length, which is used when saving account data in lookup map and account ids set
         let storage_diff = account_id
             .map(|account_id| MAX_ACCOUNT_ID_LENGTH - account_id.as_bytes().len() as u64)
             .unwrap_or_default();
 
-        REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE - storage_diff
+        REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE - ACCOUNT_ID_ENTRIES * storage_diff
+    }
+
+    /// Returns storage usage of `account_id` entries in `Contract::account_ids`
+    pub(crate) fn account_ids_storage_usage(account_id: &AccountId) -> u64 {
+        // account id is stored as element value, index entry key is a hash of fixed size
+        let storage_diff = MAX_ACCOUNT_ID_LENGTH - account_id.as_bytes().len() as u64;
+
+        ACCOUNT_IDS_MAX_ID_LENGTH_STORAGE - storage_diff
     }
 
     /// Returns required deposit by optionally provided `account_id`
@@ -49,38 +102,166 @@ impl Account {
         (Self::initial_storage_usage(account_id) as Balance * env::storage_byte_cost()).into()
     }
 
-    /// Returns storage balance
+    /// Returns amount of $NEAR locked to cover used by account storage
+    pub(crate) fn locked_balance(&self) -> Balance {
+        self.storage_usage as Balance * env::storage_byte_cost()
+    }
+
+    /// Returns part of storage balance credited for deposited tokens
+    pub(crate) fn credited_balance(&self) -> Balance {
+        self.token_credit
+            .as_ref()
+            .map(|token_credit| token_credit.storage_balance)
+            .unwrap_or_default()
+    }
+
+    /// Returns storage balance, sponsored and credited balances aren't available to the account
     pub(crate) fn storage_balance(&self) -> StorageBalance {
+        let unavailable_balance = self
+            .locked_balance()
+            .max(self.sponsored_balance + self.credited_balance());
+
         StorageBalance {
             total: self.storage_balance.into(),
             available: self
                 .storage_balance
-                .saturating_sub(self.storage_usage as Balance * env::storage_byte_cost())
+                .saturating_sub(unavailable_balance)
                 .into(),
         }
     }
+
+    /// Returns part of credited balance, which isn't locked to cover used storage and could be withdrawn in tokens
+    pub(crate) fn available_credited_balance(&self) -> Balance {
+        self.storage_balance
+            .saturating_sub(self.locked_balance().max(self.sponsored_balance))
+            .min(self.credited_balance())
+    }
+
+    /// Asserts if the account is frozen
+    pub(crate) fn assert_not_frozen(&self) {
+        require!(!self.frozen, "Account is frozen");
+    }
+
+    /// Returns storage usage of the provided `sponsor_id` in account data
+    pub(crate) fn sponsor_storage_usage(sponsor_id: &AccountId) -> StorageUsage {
+        U32_STORAGE + sponsor_id.as_bytes().len() as StorageUsage
+    }
+
+    /// Sets `sponsor_id` of the account and accounts its storage usage
+    pub(crate) fn set_sponsor(&mut self, sponsor_id: AccountId) {
+        if let Some(current_sponsor_id) = self.sponsor_id.take() {
+            self.storage_usage -= Self::sponsor_storage_usage(&current_sponsor_id);
+        }
+
+        self.storage_usage += Self::sponsor_storage_usage(&sponsor_id);
+        self.sponsor_id = Some(sponsor_id);
+    }
+
+    /// Returns storage usage of the token credit by provided `token_id` in account data
+    pub(crate) fn token_credit_storage_usage(token_id: &AccountId) -> StorageUsage {
+        U32_STORAGE + token_id.as_bytes().len() as StorageUsage + 2 * U128_STORAGE
+    }
+
+    /// Credits `storage_balance` for `amount` of `token_id` tokens and accounts storage usage of the credit
+    pub(crate) fn add_token_credit(
+        &mut self,
+        token_id: AccountId,
+        amount: Balance,
+        storage_balance: Balance,
+    ) {
+        if self.token_credit.is_none() {
+            self.storage_usage += Self::token_credit_storage_usage(&token_id);
+        }
+
+        let token_credit = self.token_credit.get_or_insert(TokenCredit {
+            token_id,
+            amount: 0,
+            storage_balance: 0,
+        });
+
+        token_credit.amount = token_credit
+            .amount
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Token credit overflow"));
+        token_credit.storage_balance += storage_balance;
+
+        self.storage_balance = self
+            .storage_balance
+            .checked_add(storage_balance)
+            .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+    }
+
+    /// Removes `amount` of tokens and `storage_balance` credited for them, emptied credit is removed
+    pub(crate) fn remove_token_credit(&mut self, amount: Balance, storage_balance: Balance) {
+        let token_credit = self
+            .token_credit
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Account has no token credit"));
+
+        token_credit.amount -= amount;
+        token_credit.storage_balance -= storage_balance;
+        self.storage_balance -= storage_balance;
+
+        if token_credit.amount == 0 {
+            self.take_token_credit();
+        }
+    }
+
+    /// Removes token credit of the account and returns it, credited balance remains in storage balance
+    pub(crate) fn take_token_credit(&mut self) -> Option<TokenCredit> {
+        let token_credit = self.token_credit.take()?;
+        self.storage_usage -= Self::token_credit_storage_usage(&token_credit.token_id);
+
+        Some(token_credit)
+    }
+
+    /// Removes sponsor of the account and returns it along with the sponsored balance
+    pub(crate) fn take_sponsor(&mut self) -> Option<(AccountId, Balance)> {
+        let sponsor_id = self.sponsor_id.take()?;
+        self.storage_usage -= Self::sponsor_storage_usage(&sponsor_id);
+
+        Some((sponsor_id, std::mem::take(&mut self.sponsored_balance)))
+    }
 }
 
-impl<'a> From<&'a VAccount> for &'a Account {
-    fn from(v_acc: &'a VAccount) -> Self {
-        match v_acc {
-            VAccount::Current(account) => account,
+impl AccountV1 {
+    /// Converts legacy account data of `account_id` to current version
+    pub(crate) fn upgrade(self, account_id: &AccountId) -> Account {
+        Account {
+            storage_balance: self.storage_balance,
+            // upgraded entry grows by the size of new fields and account gets indexed in `Contract::account_ids`
+            storage_usage: self.storage_usage + ACCOUNT_STORAGE - ACCOUNT_V1_STORAGE
+                + Account::account_ids_storage_usage(account_id),
+            registered_at: 0,
+            sponsor_id: None,
+            sponsored_balance: 0,
+            frozen: false,
+            ft_balance: 0,
+            token_credit: None,
         }
     }
 }
 
-impl<'a> From<&'a mut VAccount> for &'a mut Account {
-    fn from(v_acc: &'a mut VAccount) -> Self {
-        match v_acc {
+impl VAccount {
+    /// Converts account data of `account_id` to current version
+    pub(crate) fn into_current(self, account_id: &AccountId) -> Account {
+        match self {
+            VAccount::V1(account) => account.upgrade(account_id),
             VAccount::Current(account) => account,
         }
     }
-}
 
-impl From<VAccount> for Account {
-    fn from(v_acc: VAccount) -> Self {
-        match v_acc {
+    /// Upgrades legacy account data of `account_id` to current version in place and returns it
+    ///
+    /// Upgraded entry is rewritten upon the next collection flush
+    pub(crate) fn upgrade(&mut self, account_id: &AccountId) -> &mut Account {
+        if !matches!(self, VAccount::Current(_)) {
+            *self = VAccount::Current(self.clone().into_current(account_id));
+        }
+
+        match self {
             VAccount::Current(account) => account,
+            _ => unreachable!(),
         }
     }
 }
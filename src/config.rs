@@ -0,0 +1,108 @@
+use crate::account::Account;
+use crate::events::Event;
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen, require, AccountId, Balance};
+
+/// Basis points in 100%
+const BPS_DENOMINATOR: Balance = 10_000;
+
+/// Maximum fee in basis points, which could be charged on storage deposits
+pub(crate) const MAX_DEPOSIT_FEE_BPS: u16 = 1_000;
+
+/// Contract configuration, managed by the owner
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    /// Minimum storage deposit in $NEAR required to register an account
+    ///
+    /// Storage cost of account data is required anyway, if it exceeds the configured amount
+    pub min_storage_deposit: Option<U128>,
+    /// Maximum storage balance in $NEAR of a single account, unlimited if not set
+    ///
+    /// Deposit exceeding the maximum is refunded to the payer
+    pub max_storage_balance: Option<U128>,
+    /// Whether accounts are allowed to deposit storage for other accounts
+    pub allow_deposit_for_others: bool,
+    /// Fee in basis points charged to the contract treasury on $NEAR credited by `storage_deposit`,
+    /// `storage_deposit_batch` and `storage_sponsor`
+    ///
+    /// Minimum deposit required for registration is never charged. Token deposits via `ft_on_transfer` are exempt,
+    /// as the treasury holds $NEAR only and credited tokens are returned in kind
+    #[serde(default)]
+    pub deposit_fee_bps: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            min_storage_deposit: None,
+            max_storage_balance: None,
+            allow_deposit_for_others: true,
+            deposit_fee_bps: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Asserts if configured bounds are inconsistent
+    pub(crate) fn assert_valid(&self) {
+        if let Some(max_storage_balance) = self.max_storage_balance {
+            require!(
+                max_storage_balance.0 >= self.min_deposit(None),
+                "Maximum storage balance is less than minimum storage deposit"
+            );
+        }
+
+        require!(
+            self.deposit_fee_bps <= MAX_DEPOSIT_FEE_BPS,
+            "Deposit fee exceeds maximum"
+        );
+    }
+
+    /// Returns fee charged on deposit of `amount`, rounded down
+    pub(crate) fn deposit_fee(&self, amount: Balance) -> Balance {
+        let fee_bps = self.deposit_fee_bps as Balance;
+
+        // split to avoid overflow of large amounts
+        amount / BPS_DENOMINATOR * fee_bps + amount % BPS_DENOMINATOR * fee_bps / BPS_DENOMINATOR
+    }
+
+    /// Returns minimum storage deposit required to register optionally provided `account_id`,
+    /// otherwise account with maximum id length
+    pub(crate) fn min_deposit(&self, account_id: Option<&AccountId>) -> Balance {
+        let required_deposit = Account::required_deposit(account_id).0;
+
+        self.min_storage_deposit
+            .map_or(required_deposit, |min_storage_deposit| {
+                min_storage_deposit.0.max(required_deposit)
+            })
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns current contract configuration
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
+    }
+
+    /// Replaces contract configuration
+    #[payable]
+    pub fn update_config(&mut self, config: Config) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        config.assert_valid();
+
+        Event::ConfigUpdated {
+            old_config: &self.config,
+            new_config: &config,
+        }
+        .emit();
+
+        self.config = config;
+    }
+}
@@ -1,30 +1,137 @@
 use crate::account::{Account, VAccount};
-use crate::misc::RunningState;
+use crate::config::Config;
+#[cfg(feature = "ft")]
+use crate::fungible_token::FungibleToken;
+use crate::indexed_set::IndexedSet;
+use crate::misc::{MethodGroup, PausedMethods, RunningState};
+#[cfg(feature = "mt")]
+use crate::multi_token::MultiToken;
+#[cfg(feature = "nft")]
+use crate::non_fungible_token::NonFungibleTokens;
+use crate::registration_policy::RegistrationPolicy;
+use crate::roles::RoleSet;
+use crate::source_metadata::ContractSourceMetadata;
 use crate::storage::StorageKey;
+use crate::upgrade::{
+    write_state_version, PendingUpgradeDelay, StagedCodeInfo, DEFAULT_UPGRADE_DELAY,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::store::LookupMap;
-use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
+use near_sdk::collections::LazyOption;
+use near_sdk::store::{LookupMap, UnorderedMap};
+use near_sdk::{env, near_bindgen, require, AccountId, Balance, Duration, PanicOnDefault};
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
     /// The contract's owner account id
     pub(crate) owner_id: AccountId,
+    /// Account id proposed as a new owner, which has to accept the ownership
+    pub(crate) pending_owner_id: Option<AccountId>,
     /// Contract's state, e.g. running, paused
     pub(crate) running_state: RunningState,
+    /// Method groups paused separately while the contract is running
+    pub(crate) paused_methods: PausedMethods,
     /// User versioned accounts data keyed by AccountId
     pub(crate) accounts: LookupMap<AccountId, VAccount>,
+    /// Registered account ids, used to enumerate accounts
+    ///
+    /// Accounts registered before the index was introduced (`VAccount::V1`) are missing from it
+    /// until upgrade of their data or `index_legacy_accounts`
+    pub(crate) account_ids: IndexedSet<AccountId>,
+    /// Roles granted to accounts keyed by AccountId
+    pub(crate) roles: LookupMap<AccountId, RoleSet>,
+    /// Policy of new accounts registration
+    pub(crate) registration_policy: RegistrationPolicy,
+    /// Accounts allowed to register under `RegistrationPolicy::AllowList`
+    ///
+    /// Storage of the list is paid by the owner's deposit attached to the list update
+    pub(crate) allow_list: IndexedSet<AccountId>,
+    /// Accounts denied to register under `RegistrationPolicy::DenyList`
+    ///
+    /// Storage of the list is paid by the owner's deposit attached to the list update
+    pub(crate) deny_list: IndexedSet<AccountId>,
+    /// Fungible tokens accepted for storage deposits with their rates in yoctoNEAR per smallest token unit
+    pub(crate) storage_tokens: UnorderedMap<AccountId, Balance>,
+    /// Contract code staged for deployment
+    pub(crate) staged_code: LazyOption<Vec<u8>>,
+    /// Staged code hash and unlock timestamp
+    pub(crate) staged_code_info: Option<StagedCodeInfo>,
+    /// Delay in nanoseconds between staging code and deploying it
+    pub(crate) upgrade_delay: Duration,
+    /// Lowered upgrade delay, which takes effect after the current delay
+    pub(crate) pending_upgrade_delay: Option<PendingUpgradeDelay>,
+    /// Contract source metadata [NEP-330]
+    ///
+    /// [NEP-330]: https://nomicon.io/Standards/SourceMetadata
+    pub(crate) source_metadata: ContractSourceMetadata,
+    /// Contract configuration, managed by the owner
+    pub(crate) config: Config,
+    /// Deposit fees collected by the contract, withdrawable by treasurers
+    pub(crate) treasury_balance: Balance,
+    /// $NEAR held for accounts, which failed to be transferred to them, claimable by the accounts
+    pub(crate) refunds: LookupMap<AccountId, Balance>,
+    /// Tokens held for accounts by (account id, token id), which failed to be credited back, claimable by the accounts
+    pub(crate) token_refunds: LookupMap<(AccountId, AccountId), Balance>,
+    /// Fungible token state, token balances are stored in `accounts`
+    #[cfg(feature = "ft")]
+    pub(crate) ft: FungibleToken,
+    /// Non-fungible tokens state, storage of tokens is charged to their owners
+    #[cfg(feature = "nft")]
+    pub(crate) nft: NonFungibleTokens,
+    /// Multi token state, storage of token balances is charged to their owners
+    #[cfg(feature = "mt")]
+    pub(crate) mt: MultiToken,
 }
 
 #[near_bindgen]
 impl Contract {
     /// Initializes contract
+    ///
+    /// - `owner_id`: owner of the contract, defaults to the caller
+    /// - `source_link`: link to the contract source code, reported in contract source metadata
+    /// - `config`: contract configuration, defaults to `Config::default()`
     #[init]
-    pub fn init(owner_id: Option<AccountId>) -> Self {
+    pub fn init(
+        owner_id: Option<AccountId>,
+        source_link: Option<String>,
+        config: Option<Config>,
+    ) -> Self {
+        let config = config.unwrap_or_default();
+        config.assert_valid();
+
+        let owner_id = owner_id.unwrap_or_else(env::predecessor_account_id);
+        #[cfg(feature = "nft")]
+        let nft = NonFungibleTokens::new(owner_id.clone());
+
+        write_state_version();
+
         Self {
-            owner_id: owner_id.unwrap_or_else(env::predecessor_account_id),
+            owner_id,
+            pending_owner_id: None,
             running_state: RunningState::Running,
+            paused_methods: PausedMethods::default(),
             accounts: LookupMap::new(StorageKey::Accounts),
+            account_ids: IndexedSet::new(StorageKey::AccountIds),
+            roles: LookupMap::new(StorageKey::Roles),
+            registration_policy: RegistrationPolicy::default(),
+            allow_list: IndexedSet::new(StorageKey::AllowList),
+            deny_list: IndexedSet::new(StorageKey::DenyList),
+            storage_tokens: UnorderedMap::new(StorageKey::StorageTokens),
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_code_info: None,
+            upgrade_delay: DEFAULT_UPGRADE_DELAY,
+            pending_upgrade_delay: None,
+            source_metadata: ContractSourceMetadata::new(source_link),
+            config,
+            treasury_balance: 0,
+            refunds: LookupMap::new(StorageKey::Refunds),
+            token_refunds: LookupMap::new(StorageKey::TokenRefunds),
+            #[cfg(feature = "ft")]
+            ft: FungibleToken::new(),
+            #[cfg(feature = "nft")]
+            nft,
+            #[cfg(feature = "mt")]
+            mt: MultiToken::new(),
         }
     }
 }
@@ -38,6 +145,12 @@ impl Contract {
         );
     }
 
+    /// Checks if contract is at running state and `group` methods aren't paused
+    pub(crate) fn assert_methods_running(&self, group: MethodGroup) {
+        self.assert_contract_running();
+        require!(!self.paused_methods.contains(group), "Methods paused");
+    }
+
     /// Asserts if the caller is not an owner of the contract
     pub(crate) fn assert_owner(&self) {
         require!(self.is_owner(&env::predecessor_account_id()), "Not allowed");
@@ -48,36 +161,67 @@ impl Contract {
         account_id == &self.owner_id
     }
 
+    /// Writes pending changes of the contract collections to storage
+    pub(crate) fn flush(&mut self) {
+        self.accounts.flush();
+        self.account_ids.flush();
+        self.roles.flush();
+        self.allow_list.flush();
+        self.deny_list.flush();
+        self.storage_tokens.flush();
+        self.refunds.flush();
+        self.token_refunds.flush();
+    }
+
+    /// Registers new account by provided `account_id`
+    pub(crate) fn insert_account(&mut self, account_id: &AccountId, account: Account) {
+        self.accounts.insert(account_id.clone(), account.into());
+        self.account_ids.insert(account_id.clone());
+    }
+
     /// Returns reference to account by provided `account_id`
     pub(crate) fn remove_account(
         &mut self,
         account_id: &AccountId,
     ) -> Result<Account, &'static str> {
-        self.accounts
+        let account = self
+            .accounts
             .remove(account_id)
-            .map(Account::from)
-            .ok_or("Account is not registered")
+            .map(|account| account.into_current(account_id))
+            .ok_or("Account is not registered")?;
+
+        self.account_ids.remove(account_id);
+
+        Ok(account)
     }
 
-    /// Returns reference to account by provided `account_id`
-    pub(crate) fn get_account<'a>(
-        &'a self,
-        account_id: &'a AccountId,
-    ) -> Result<&'a Account, &'static str> {
+    /// Returns account by provided `account_id`, legacy account data is converted to current version
+    pub(crate) fn get_account(&self, account_id: &AccountId) -> Result<Account, &'static str> {
         self.accounts
             .get(account_id)
-            .map(<&'a Account>::from)
+            .cloned()
+            .map(|account| account.into_current(account_id))
             .ok_or("Account is not registered")
     }
 
     /// Returns mutable reference to account by provided `account_id`
+    ///
+    /// Legacy account data is upgraded to current version and rewritten
     pub(crate) fn get_account_mut<'a>(
         &'a mut self,
         account_id: &'a AccountId,
     ) -> Result<&'a mut Account, &'static str> {
-        self.accounts
+        let account = self
+            .accounts
             .get_mut(account_id)
-            .map(<&'a mut Account>::from)
-            .ok_or("Account is not registered")
+            .ok_or("Account is not registered")?;
+
+        // accounts of the first version were registered before `account_ids` index was introduced,
+        // it's the only legacy version, so every legacy account gets indexed upon upgrade
+        if matches!(account, VAccount::V1(_)) {
+            self.account_ids.insert(account_id.clone());
+        }
+
+        Ok(account.upgrade(account_id))
     }
 }
@@ -0,0 +1,232 @@
+use crate::config::Config;
+use crate::registration_policy::RegistrationPolicy;
+use crate::roles::Role;
+use near_sdk::json_types::{Base58CryptoHash, U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, serde_json, AccountId};
+
+/// Standard name of the contract events [NEP-297]
+pub(crate) const EVENT_STANDARD: &str = "template-contract";
+
+/// Version of the contract events standard
+pub(crate) const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Contract events, logged in [NEP-297] format
+///
+/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub(crate) enum Event<'a> {
+    /// Current owner proposed a new owner of the contract
+    OwnerProposed {
+        owner_id: &'a AccountId,
+        proposed_owner_id: &'a AccountId,
+    },
+    /// Current owner cancelled pending ownership proposal
+    OwnerProposalCancelled {
+        owner_id: &'a AccountId,
+        proposed_owner_id: &'a AccountId,
+    },
+    /// Proposed owner accepted the ownership
+    OwnershipTransferred {
+        old_owner_id: &'a AccountId,
+        new_owner_id: &'a AccountId,
+    },
+    /// Role was granted to account
+    RoleGranted {
+        role: Role,
+        account_id: &'a AccountId,
+        sender_id: &'a AccountId,
+    },
+    /// Role was revoked from account, or renounced by account itself
+    RoleRevoked {
+        role: Role,
+        account_id: &'a AccountId,
+        sender_id: &'a AccountId,
+    },
+    /// New contract code was staged for deployment
+    CodeStaged {
+        hash: &'a Base58CryptoHash,
+        unlock_timestamp: &'a U64,
+    },
+    /// Owner updated upgrade delay, which takes effect at `unlock_timestamp`
+    UpgradeDelayUpdated {
+        upgrade_delay: U64,
+        unlock_timestamp: U64,
+    },
+    /// Staged contract code was deployed
+    StagedCodeDeployed { hash: &'a Base58CryptoHash },
+    /// Staged contract code was cancelled
+    StagedCodeCancelled { hash: &'a Base58CryptoHash },
+    /// Account was registered with initial storage balance
+    StorageRegister {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Storage balance of registered account was increased
+    StorageDeposit {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Available storage balance was withdrawn by account
+    StorageWithdraw {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Account was unregistered, its storage balance was transferred to it
+    StorageUnregister {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Account was unregistered with `force` flag, its storage balance was transferred to it
+    StorageForceUnregister {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Sponsor deposited storage balance for account, which isn't withdrawable by the account
+    StorageSponsor {
+        sponsor_id: &'a AccountId,
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Sponsored balance of unregistered account was returned to sponsor
+    StorageSponsorRefund {
+        sponsor_id: &'a AccountId,
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Transfer of sponsored balance to sponsor failed, `amount` was returned to account sponsored balance
+    StorageSponsorRefundRollback {
+        sponsor_id: &'a AccountId,
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Owner froze account, blocking its deposits and withdrawals
+    AccountFrozen { account_id: &'a AccountId },
+    /// Owner unfroze previously frozen account
+    AccountUnfrozen { account_id: &'a AccountId },
+    /// Owner unregistered account, its storage balance was transferred to beneficiary
+    AccountForceUnregistered {
+        account_id: &'a AccountId,
+        beneficiary_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Owner updated policy of new accounts registration
+    RegistrationPolicyUpdated {
+        old_policy: RegistrationPolicy,
+        new_policy: RegistrationPolicy,
+    },
+    /// Owner added accounts to the allow list
+    AllowListAdd { account_ids: &'a [AccountId] },
+    /// Owner removed accounts from the allow list
+    AllowListRemove { account_ids: &'a [AccountId] },
+    /// Owner added accounts to the deny list
+    DenyListAdd { account_ids: &'a [AccountId] },
+    /// Owner removed accounts from the deny list
+    DenyListRemove { account_ids: &'a [AccountId] },
+    /// Owner updated contract configuration
+    ConfigUpdated {
+        old_config: &'a Config,
+        new_config: &'a Config,
+    },
+    /// Transfer of withdrawn storage balance failed, `amount` was returned to account storage balance
+    StorageWithdrawRollback {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Transfer to unregistered account failed, `amount` was held for the account until it's claimed
+    RefundHeld {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Account claimed `amount` held for it
+    RefundClaimed {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Returned tokens couldn't be credited to account storage balance, `amount` was held for the account until it's claimed
+    TokenRefundHeld {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Account claimed `amount` of tokens held for it
+    TokenRefundClaimed {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Transfer of unregistered account storage balance failed, account was registered again with `amount`
+    StorageUnregisterRollback {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Deposit fee `amount` was charged on storage deposit of account to the contract treasury
+    DepositFeeCollected {
+        account_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Treasurer withdrew `amount` of collected fees to `receiver_id`
+    TreasuryWithdraw {
+        receiver_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Transfer of withdrawn fees failed, `amount` was returned to the contract treasury
+    TreasuryWithdrawRollback {
+        receiver_id: &'a AccountId,
+        amount: U128,
+    },
+    /// Owner updated rate in yoctoNEAR per smallest unit of the token accepted for storage deposits, `None` rate removes the token
+    StorageTokenUpdated {
+        token_id: &'a AccountId,
+        rate: Option<U128>,
+    },
+    /// Account storage balance was credited with `storage_amount` for `amount` of deposited tokens
+    StorageTokenDeposit {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+        storage_amount: U128,
+    },
+    /// Deposited tokens were returned to account, its storage balance was reduced by `storage_amount`
+    StorageTokenWithdraw {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+        storage_amount: U128,
+    },
+    /// Transfer of withdrawn tokens failed, they were credited to account storage balance again
+    StorageTokenWithdrawRollback {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+        storage_amount: U128,
+    },
+}
+
+/// Event log envelope with standard name and version
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+impl Event<'_> {
+    /// Logs event with `EVENT_JSON:` prefix
+    pub(crate) fn emit(&self) {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        };
+
+        let json = serde_json::to_string(&log)
+            .unwrap_or_else(|_| env::panic_str("Event serialization failed"));
+
+        env::log_str(&format!("EVENT_JSON:{}", json));
+    }
+}
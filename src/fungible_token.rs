@@ -0,0 +1,286 @@
+use crate::account::Account;
+use crate::storage::StorageKey;
+use crate::{Contract, ContractExt};
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::events::{FtBurn, FtMint, FtTransfer};
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider,
+};
+use near_contract_standards::fungible_token::receiver::ext_ft_receiver;
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, require, serde_json, AccountId, Balance, Gas,
+    PromiseOrValue, PromiseResult,
+};
+
+/// Gas reserved for `ft_resolve_transfer` callback
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+
+/// Gas reserved for `ft_transfer_call` execution apart from `ft_on_transfer` call of the receiver
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+/// Fungible token [NEP-141] state, token balances are stored in the account registry
+///
+/// [NEP-141]: https://nomicon.io/Standards/Tokens/FungibleToken/Core
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct FungibleToken {
+    /// Total supply of the token
+    pub(crate) total_supply: Balance,
+    /// Token metadata [NEP-148], set by the owner
+    ///
+    /// [NEP-148]: https://nomicon.io/Standards/Tokens/FungibleToken/Metadata
+    pub(crate) metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+impl FungibleToken {
+    pub(crate) fn new() -> Self {
+        Self {
+            total_supply: 0,
+            metadata: LazyOption::new(StorageKey::FtMetadata, None),
+        }
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    /// Transfers `amount` of tokens to `receiver_id`, both accounts have to be registered
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.0, memo.as_deref());
+    }
+
+    /// Transfers `amount` of tokens to `receiver_id` and calls its `ft_on_transfer`
+    ///
+    /// Unused amount returned by the receiver is refunded in `ft_resolve_transfer`
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        require!(
+            env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL,
+            "More gas is required"
+        );
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.0, memo.as_deref());
+
+        ext_ft_receiver::ext(receiver_id.clone())
+            .with_static_gas(env::prepaid_gas() - GAS_FOR_FT_TRANSFER_CALL)
+            .ft_on_transfer(sender_id.clone(), amount, msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount),
+            )
+            .into()
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.ft.total_supply.into()
+    }
+
+    /// Returns token balance of `account_id`, zero if account is not registered
+    fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.get_account(&account_id)
+            .map(|account| account.ft_balance)
+            .unwrap_or_default()
+            .into()
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    /// Callback of `ft_transfer_call`, refunds unused amount to the sender and returns used amount
+    ///
+    /// Refund is burned if the sender was unregistered in the meantime
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let amount = amount.0;
+
+        let unused_amount = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(|unused_amount| unused_amount.0.min(amount))
+                .unwrap_or(amount),
+            PromiseResult::Failed => amount,
+        };
+
+        let refund_amount = match self.get_account_mut(&receiver_id) {
+            Ok(receiver) => {
+                let refund_amount = receiver.ft_balance.min(unused_amount);
+                receiver.ft_balance -= refund_amount;
+                refund_amount
+            }
+            Err(_) => 0,
+        };
+
+        if refund_amount == 0 {
+            return amount.into();
+        }
+
+        match self.get_account_mut(&sender_id) {
+            Ok(sender) => {
+                sender.ft_balance = sender
+                    .ft_balance
+                    .checked_add(refund_amount)
+                    .unwrap_or_else(|| env::panic_str("Token balance overflow"));
+
+                FtTransfer {
+                    old_owner_id: &receiver_id,
+                    new_owner_id: &sender_id,
+                    amount: &refund_amount.into(),
+                    memo: Some("refund"),
+                }
+                .emit();
+
+                (amount - refund_amount).into()
+            }
+            Err(_) => {
+                self.ft.total_supply -= refund_amount;
+
+                FtBurn {
+                    owner_id: &receiver_id,
+                    amount: &refund_amount.into(),
+                    memo: Some("refund"),
+                }
+                .emit();
+
+                amount.into()
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenMetadataProvider for Contract {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        self.ft
+            .metadata
+            .get()
+            .unwrap_or_else(|| env::panic_str("Token metadata is not set"))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets token metadata [NEP-148]
+    ///
+    /// [NEP-148]: https://nomicon.io/Standards/Tokens/FungibleToken/Metadata
+    #[payable]
+    pub fn set_ft_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        metadata.assert_valid();
+        self.ft.metadata.set(&metadata);
+    }
+
+    /// Mints `amount` of tokens to registered `account_id`
+    #[payable]
+    pub fn ft_mint(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(amount.0 > 0, "The amount should be a positive number");
+
+        self.ft.total_supply = self
+            .ft
+            .total_supply
+            .checked_add(amount.0)
+            .unwrap_or_else(|| env::panic_str("Total supply overflow"));
+
+        let account = self
+            .get_account_mut(&account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        account.ft_balance += amount.0;
+
+        FtMint {
+            owner_id: &account_id,
+            amount: &amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Moves `amount` of tokens from `sender_id` to `receiver_id`, frozen sender isn't able to transfer
+    fn internal_ft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(amount > 0, "The amount should be a positive number");
+
+        let sender = self
+            .get_account_mut(sender_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        sender.assert_not_frozen();
+        sender.ft_balance = sender
+            .ft_balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+
+        let receiver = self
+            .get_account_mut(receiver_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        receiver.ft_balance = receiver
+            .ft_balance
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Token balance overflow"));
+
+        FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &amount.into(),
+            memo,
+        }
+        .emit();
+    }
+
+    /// Burns token balance of the unregistered `account`
+    pub(crate) fn burn_unregistered_ft_balance(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+    ) {
+        let amount = std::mem::take(&mut account.ft_balance);
+        if amount == 0 {
+            return;
+        }
+
+        self.ft.total_supply -= amount;
+
+        FtBurn {
+            owner_id: account_id,
+            amount: &amount.into(),
+            memo: Some("unregister"),
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,82 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::store::key::Sha256;
+use near_sdk::store::{LookupMap, Vector};
+use near_sdk::IntoStorageKey;
+
+/// Set with elements addressable by index, so a page of elements is read without walking the preceding ones
+///
+/// Elements are stored in a vector along with a map of their indices.
+/// Removed element is replaced by the last one, so removal changes order of the elements
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct IndexedSet<T>
+where
+    T: BorshSerialize + Ord,
+{
+    /// Set elements in order of insertion, up to removals
+    elements: Vector<T>,
+    /// Index of each element in `elements`
+    indices: LookupMap<T, u32, Sha256>,
+}
+
+impl<T> IndexedSet<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    /// Creates new set, elements and indices are stored under the `prefix` with `v` and `m` suffixes respectively
+    pub(crate) fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        let prefix = prefix.into_storage_key();
+
+        Self {
+            elements: Vector::new([prefix.as_slice(), b"v"].concat()),
+            indices: LookupMap::with_hasher([prefix.as_slice(), b"m"].concat()),
+        }
+    }
+
+    /// Returns number of elements in the set
+    pub(crate) fn len(&self) -> u32 {
+        self.elements.len()
+    }
+
+    /// Returns whether the set contains `element`
+    pub(crate) fn contains(&self, element: &T) -> bool {
+        self.indices.contains_key(element)
+    }
+
+    /// Returns iterator over elements starting from `from_index`, preceding elements aren't read
+    pub(crate) fn iter_from(&self, from_index: u32) -> impl Iterator<Item = &T> {
+        (from_index..self.elements.len()).filter_map(|index| self.elements.get(index))
+    }
+
+    /// Inserts `element` and returns whether it wasn't present in the set
+    pub(crate) fn insert(&mut self, element: T) -> bool {
+        if self.indices.contains_key(&element) {
+            return false;
+        }
+
+        self.indices.insert(element.clone(), self.elements.len());
+        self.elements.push(element);
+
+        true
+    }
+
+    /// Removes `element` and returns whether it was present in the set, the last element takes its index
+    pub(crate) fn remove(&mut self, element: &T) -> bool {
+        let index = match self.indices.remove(element) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.elements.swap_remove(index);
+        if let Some(moved) = self.elements.get(index) {
+            self.indices.insert(moved.clone(), index);
+        }
+
+        true
+    }
+
+    /// Writes pending changes to storage
+    pub(crate) fn flush(&mut self) {
+        self.elements.flush();
+        self.indices.flush();
+    }
+}
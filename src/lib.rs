@@ -1,10 +1,29 @@
 mod account;
+mod config;
 mod contract;
+mod events;
+#[cfg(feature = "ft")]
+mod fungible_token;
+mod indexed_set;
 mod misc;
+mod moderation;
+#[cfg(feature = "mt")]
+mod multi_token;
+#[cfg(feature = "nft")]
+mod non_fungible_token;
+mod owner;
+mod refunds;
+mod registration_policy;
+mod roles;
+mod source_metadata;
 mod storage;
 mod storage_management;
+mod storage_token;
 mod storage_tracker;
 #[cfg(test)]
 mod tests;
+mod treasury;
+mod upgrade;
+mod views;
 
 pub use contract::*;
@@ -1,13 +1,70 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub enum RunningState {
     Running,
     Paused,
 }
 
+/// Groups of storage management methods, which could be paused separately
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum MethodGroup {
+    /// `storage_deposit`, `storage_deposit_batch`, `storage_sponsor` and token deposits via `ft_on_transfer`
+    Deposits,
+    /// `storage_withdraw` and `storage_withdraw_token`
+    Withdrawals,
+    /// `storage_unregister`
+    Unregister,
+    /// `storage_balance_of`
+    Views,
+}
+
+impl MethodGroup {
+    const ALL: [MethodGroup; 4] = [
+        MethodGroup::Deposits,
+        MethodGroup::Withdrawals,
+        MethodGroup::Unregister,
+        MethodGroup::Views,
+    ];
+
+    fn flag(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// Set of paused method groups, stored as a bitset
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub(crate) struct PausedMethods(u8);
+
+impl PausedMethods {
+    pub(crate) fn contains(&self, group: MethodGroup) -> bool {
+        self.0 & group.flag() != 0
+    }
+
+    pub(crate) fn insert(&mut self, group: MethodGroup) -> bool {
+        let inserted = !self.contains(group);
+        self.0 |= group.flag();
+        inserted
+    }
+
+    pub(crate) fn remove(&mut self, group: MethodGroup) -> bool {
+        let removed = self.contains(group);
+        self.0 &= !group.flag();
+        removed
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = MethodGroup> + '_ {
+        MethodGroup::ALL
+            .into_iter()
+            .filter(|group| self.contains(*group))
+    }
+}
+
 pub(crate) trait Hash {
     fn hash(&self) -> Vec<u8>;
 }
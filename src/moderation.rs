@@ -0,0 +1,68 @@
+use crate::events::Event;
+use crate::{Contract, ContractExt};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId};
+
+#[near_bindgen]
+impl Contract {
+    /// Freezes `account_id`, blocking its deposits and withdrawals while keeping its funds
+    #[payable]
+    pub fn freeze_account(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account = self
+            .get_account_mut(&account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+
+        require!(!account.frozen, "Account is already frozen");
+        account.frozen = true;
+
+        Event::AccountFrozen {
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    /// Unfreezes previously frozen `account_id`
+    #[payable]
+    pub fn unfreeze_account(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account = self
+            .get_account_mut(&account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+
+        require!(account.frozen, "Account is not frozen");
+        account.frozen = false;
+
+        Event::AccountUnfrozen {
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    /// Unregisters `account_id` and transfers its storage balance to `beneficiary_id`
+    ///
+    /// Sponsored balance is returned to the sponsor, tokens of the account are burned
+    #[payable]
+    pub fn force_unregister_account(&mut self, account_id: AccountId, beneficiary_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account = self
+            .remove_account(&account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+
+        let amount = self
+            .transfer_unregistered_balance(&account_id, account, beneficiary_id.clone())
+            .into();
+
+        Event::AccountForceUnregistered {
+            account_id: &account_id,
+            beneficiary_id: &beneficiary_id,
+            amount,
+        }
+        .emit();
+    }
+}
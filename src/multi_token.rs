@@ -0,0 +1,586 @@
+use crate::account::Account;
+use crate::storage::StorageKey;
+use crate::storage_tracker::StorageUsageTrackerData;
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, require, serde_json, AccountId, Balance,
+    Gas, PromiseOrValue, PromiseResult,
+};
+
+/// Standard name of the multi token events [NEP-245]
+///
+/// [NEP-245]: https://nomicon.io/Standards/Tokens/MultiToken/Core
+const MT_EVENT_STANDARD: &str = "nep245";
+
+/// Version of the multi token events standard
+const MT_EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Gas reserved for `mt_resolve_transfer` callback
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Gas reserved for `mt_transfer_call` execution apart from `mt_on_transfer` call of the receiver
+const GAS_FOR_MT_TRANSFER_CALL: Gas = Gas(30_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+/// Multi token id
+pub type TokenId = String;
+
+/// Approval of the token transfer by the owner and approval id, approvals aren't supported
+type Approval = Option<(AccountId, u64)>;
+
+/// Receiver interface of `mt_transfer_call`
+#[allow(dead_code)]
+#[ext_contract(ext_mt_receiver)]
+trait MultiTokenReceiver {
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+/// Multi token [NEP-245] state
+///
+/// Token balances of an account are stored in its own collection, storage of which is charged
+/// to the account storage balance, see `Contract::track_storage_usage`
+///
+/// [NEP-245]: https://nomicon.io/Standards/Tokens/MultiToken/Core
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct MultiToken {
+    /// Positive token balances by account, account entry is removed with its last balance
+    pub(crate) balances: LookupMap<AccountId, UnorderedMap<TokenId, Balance>>,
+    /// Total supply by token id
+    pub(crate) total_supply: LookupMap<TokenId, Balance>,
+}
+
+impl MultiToken {
+    pub(crate) fn new() -> Self {
+        Self {
+            balances: LookupMap::new(StorageKey::MtBalances),
+            total_supply: LookupMap::new(StorageKey::MtTotalSupply),
+        }
+    }
+
+    /// Returns `token_id` balance of `account_id`
+    fn balance_of(&self, account_id: &AccountId, token_id: &TokenId) -> Balance {
+        self.balances
+            .get(account_id)
+            .and_then(|balances| balances.get(token_id))
+            .unwrap_or_default()
+    }
+
+    /// Increases `token_id` balance of `account_id` by `amount`
+    fn deposit(&mut self, account_id: &AccountId, token_id: &TokenId, amount: Balance) {
+        let mut balances = self.balances.get(account_id).unwrap_or_else(|| {
+            UnorderedMap::new(StorageKey::MtAccountBalances {
+                account_hash: env::sha256(account_id.as_bytes()),
+            })
+        });
+
+        let balance = balances
+            .get(token_id)
+            .unwrap_or_default()
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Token balance overflow"));
+        balances.insert(token_id, &balance);
+
+        self.balances.insert(account_id, &balances);
+    }
+
+    /// Decreases `token_id` balance of `account_id` by `amount`, zero balance is removed
+    fn withdraw(&mut self, account_id: &AccountId, token_id: &TokenId, amount: Balance) {
+        let mut balances = self
+            .balances
+            .get(account_id)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+
+        let balance = balances
+            .get(token_id)
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+
+        if balance > 0 {
+            balances.insert(token_id, &balance);
+        } else {
+            balances.remove(token_id);
+        }
+
+        if balances.is_empty() {
+            self.balances.remove(account_id);
+        } else {
+            self.balances.insert(account_id, &balances);
+        }
+    }
+}
+
+/// Multi token events [NEP-245], logged in [NEP-297] format
+///
+/// [NEP-245]: https://nomicon.io/Standards/Tokens/MultiToken/Events
+/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+enum MtEvent<'a> {
+    /// Tokens were minted to `owner_id`
+    #[serde(rename = "mt_mint")]
+    Mint([MtOwnerTokens<'a>; 1]),
+    /// Tokens were transferred from `old_owner_id` to `new_owner_id`
+    #[serde(rename = "mt_transfer")]
+    Transfer([MtTransfer<'a>; 1]),
+    /// Tokens of `owner_id` were burned
+    #[serde(rename = "mt_burn")]
+    Burn([MtOwnerTokens<'a>; 1]),
+}
+
+/// Data of `mt_mint` and `mt_burn` events
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct MtOwnerTokens<'a> {
+    owner_id: &'a AccountId,
+    token_ids: &'a [TokenId],
+    amounts: &'a [U128],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+}
+
+/// Data of `mt_transfer` event
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct MtTransfer<'a> {
+    old_owner_id: &'a AccountId,
+    new_owner_id: &'a AccountId,
+    token_ids: &'a [TokenId],
+    amounts: &'a [U128],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+}
+
+/// Event log envelope with standard name and version
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct MtEventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a MtEvent<'a>,
+}
+
+impl MtEvent<'_> {
+    /// Logs event with `EVENT_JSON:` prefix
+    fn emit(&self) {
+        let log = MtEventLog {
+            standard: MT_EVENT_STANDARD,
+            version: MT_EVENT_STANDARD_VERSION,
+            event: self,
+        };
+
+        let json = serde_json::to_string(&log)
+            .unwrap_or_else(|_| env::panic_str("Event serialization failed"));
+
+        env::log_str(&format!("EVENT_JSON:{}", json));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers `amount` of `token_id` to registered `receiver_id`
+    ///
+    /// Storage of the receiver balance is charged to the receiver, storage of the emptied sender balance is released
+    #[payable]
+    pub fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Approval,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_contract_running();
+        assert_no_approvals([&approval]);
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_mt_batch_transfer(
+            &sender_id,
+            &receiver_id,
+            &[token_id],
+            &[amount],
+            memo.as_deref(),
+        );
+    }
+
+    /// Transfers `amounts` of `token_ids` to registered `receiver_id` in a single batch
+    #[payable]
+    pub fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Approval>>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_contract_running();
+        assert_no_approvals(approvals.iter().flatten());
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_mt_batch_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_ids,
+            &amounts,
+            memo.as_deref(),
+        );
+    }
+
+    /// Transfers `amount` of `token_id` to `receiver_id` and calls its `mt_on_transfer`
+    ///
+    /// Unused amount returned by the receiver is refunded in `mt_resolve_transfer`
+    #[payable]
+    pub fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Approval,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        self.assert_contract_running();
+        assert_no_approvals([&approval]);
+
+        self.internal_mt_batch_transfer_call(receiver_id, vec![token_id], vec![amount], memo, msg)
+    }
+
+    /// Transfers `amounts` of `token_ids` to `receiver_id` in a single batch and calls its `mt_on_transfer`
+    ///
+    /// Unused amounts returned by the receiver are refunded in `mt_resolve_transfer`
+    #[payable]
+    pub fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Approval>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        self.assert_contract_running();
+        assert_no_approvals(approvals.iter().flatten());
+
+        self.internal_mt_batch_transfer_call(receiver_id, token_ids, amounts, memo, msg)
+    }
+
+    /// Callback of `mt_transfer_call` and `mt_batch_transfer_call`, refunds unused amounts
+    /// to the sender and returns used amounts
+    ///
+    /// Refund is burned if the sender was unregistered in the meantime and kept by the receiver
+    /// if the sender's storage balance doesn't cover storage of the refunded balance anymore
+    #[private]
+    pub fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128> {
+        let unused_amounts = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => serde_json::from_slice::<Vec<U128>>(&value)
+                .ok()
+                .filter(|unused_amounts| unused_amounts.len() == amounts.len())
+                .unwrap_or_else(|| amounts.clone()),
+            PromiseResult::Failed => amounts.clone(),
+        };
+
+        let mut used_amounts = Vec::with_capacity(amounts.len());
+
+        for (i, token_id) in token_ids.iter().enumerate() {
+            let amount = amounts[i].0;
+            let sender_id = &previous_owner_ids[i];
+
+            let refund_amount = self
+                .mt
+                .balance_of(&receiver_id, token_id)
+                .min(unused_amounts[i].0.min(amount));
+
+            if refund_amount == 0 {
+                used_amounts.push(amount.into());
+                continue;
+            }
+
+            // receiver is registered while it owns tokens
+            self.track_storage_usage(&receiver_id, |contract| {
+                contract.mt.withdraw(&receiver_id, token_id, refund_amount)
+            });
+
+            let token_ids = [token_id.clone()];
+            let amounts = [refund_amount.into()];
+
+            if self.get_account(sender_id).is_ok() {
+                let (_, is_covered) = self.try_track_storage_usage(sender_id, |contract| {
+                    contract.mt.deposit(sender_id, token_id, refund_amount)
+                });
+
+                if !is_covered {
+                    // releasing the refunded balance is always covered, as well as restoring the receiver one
+                    self.try_track_storage_usage(sender_id, |contract| {
+                        contract.mt.withdraw(sender_id, token_id, refund_amount)
+                    });
+                    self.track_storage_usage(&receiver_id, |contract| {
+                        contract.mt.deposit(&receiver_id, token_id, refund_amount)
+                    });
+
+                    used_amounts.push(amount.into());
+                    continue;
+                }
+
+                MtEvent::Transfer([MtTransfer {
+                    old_owner_id: &receiver_id,
+                    new_owner_id: sender_id,
+                    token_ids: &token_ids,
+                    amounts: &amounts,
+                    memo: Some("refund"),
+                }])
+                .emit();
+
+                used_amounts.push((amount - refund_amount).into());
+            } else {
+                self.decrease_mt_supply(token_id, refund_amount);
+
+                MtEvent::Burn([MtOwnerTokens {
+                    owner_id: &receiver_id,
+                    token_ids: &token_ids,
+                    amounts: &amounts,
+                    memo: Some("refund"),
+                }])
+                .emit();
+
+                used_amounts.push(amount.into());
+            }
+        }
+
+        used_amounts
+    }
+
+    /// Mints `amount` of `token_id` to registered `account_id`, storage of the balance is charged to the account
+    #[payable]
+    pub fn mt_mint(
+        &mut self,
+        account_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(amount.0 > 0, "The amount should be a positive number");
+
+        let total_supply = self
+            .mt
+            .total_supply
+            .get(&token_id)
+            .unwrap_or_default()
+            .checked_add(amount.0)
+            .unwrap_or_else(|| env::panic_str("Total supply overflow"));
+        self.mt.total_supply.insert(&token_id, &total_supply);
+
+        self.track_storage_usage(&account_id, |contract| {
+            contract.mt.deposit(&account_id, &token_id, amount.0)
+        });
+
+        MtEvent::Mint([MtOwnerTokens {
+            owner_id: &account_id,
+            token_ids: &[token_id],
+            amounts: &[amount],
+            memo: memo.as_deref(),
+        }])
+        .emit();
+    }
+
+    /// Returns `token_id` balance of `account_id`
+    pub fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+        self.mt.balance_of(&account_id, &token_id).into()
+    }
+
+    /// Returns balances of `account_id` for each of `token_ids`
+    pub fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128> {
+        token_ids
+            .iter()
+            .map(|token_id| self.mt.balance_of(&account_id, token_id).into())
+            .collect()
+    }
+
+    /// Returns total supply of `token_id`, `None` if the token was never minted
+    pub fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
+        self.mt.total_supply.get(&token_id).map(U128)
+    }
+
+    /// Returns total supply for each of `token_ids`
+    pub fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>> {
+        token_ids
+            .into_iter()
+            .map(|token_id| self.mt_supply(token_id))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Moves `amounts` of `token_ids` from `sender_id` to `receiver_id`, frozen sender isn't able to transfer
+    ///
+    /// The same token id may repeat in the batch, each of its amounts is moved in order
+    fn internal_mt_batch_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[U128],
+        memo: Option<&str>,
+    ) {
+        require!(!token_ids.is_empty(), "Token ids should not be empty");
+        require!(
+            token_ids.len() == amounts.len(),
+            "Number of token ids and amounts should be equal"
+        );
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(
+            amounts.iter().all(|amount| amount.0 > 0),
+            "The amount should be a positive number"
+        );
+
+        self.get_account(sender_id)
+            .unwrap_or_else(|e| env::panic_str(e))
+            .assert_not_frozen();
+
+        self.track_storage_usage(sender_id, |contract| {
+            for (token_id, amount) in token_ids.iter().zip(amounts) {
+                contract.mt.withdraw(sender_id, token_id, amount.0);
+            }
+        });
+
+        self.track_storage_usage(receiver_id, |contract| {
+            for (token_id, amount) in token_ids.iter().zip(amounts) {
+                contract.mt.deposit(receiver_id, token_id, amount.0);
+            }
+        });
+
+        MtEvent::Transfer([MtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            token_ids,
+            amounts,
+            memo,
+        }])
+        .emit();
+    }
+
+    /// Transfers `amounts` of `token_ids` to `receiver_id`, calls its `mt_on_transfer` and resolves the transfer
+    fn internal_mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        require!(
+            env::prepaid_gas() > GAS_FOR_MT_TRANSFER_CALL,
+            "More gas is required"
+        );
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_mt_batch_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_ids,
+            &amounts,
+            memo.as_deref(),
+        );
+
+        let previous_owner_ids = vec![sender_id.clone(); token_ids.len()];
+
+        ext_mt_receiver::ext(receiver_id.clone())
+            .with_static_gas(env::prepaid_gas() - GAS_FOR_MT_TRANSFER_CALL)
+            .mt_on_transfer(
+                sender_id,
+                previous_owner_ids.clone(),
+                token_ids.clone(),
+                amounts.clone(),
+                msg,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(previous_owner_ids, receiver_id, token_ids, amounts),
+            )
+            .into()
+    }
+
+    /// Decreases total supply of `token_id` by burned `amount`
+    fn decrease_mt_supply(&mut self, token_id: &TokenId, amount: Balance) {
+        let total_supply = self.mt.total_supply.get(token_id).unwrap_or_default() - amount;
+        self.mt.total_supply.insert(token_id, &total_supply);
+    }
+
+    /// Returns whether `account_id` owns any multi token, so it can't be unregistered without `force`
+    pub(crate) fn owns_mt_balances(&self, account_id: &AccountId) -> bool {
+        self.mt.balances.contains_key(account_id)
+    }
+
+    /// Burns token balances of the unregistered `account`, their storage is released from its storage usage
+    pub(crate) fn burn_unregistered_mt_balances(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+    ) {
+        let balances = match self.mt.balances.get(account_id) {
+            Some(balances) => balances.to_vec(),
+            None => return,
+        };
+
+        self.flush(); // flush content before use env::storage_usage()
+        let storage_tracker = StorageUsageTrackerData::default().track();
+
+        for (token_id, amount) in &balances {
+            self.mt.withdraw(account_id, token_id, *amount);
+            self.decrease_mt_supply(token_id, *amount);
+        }
+
+        self.flush();
+        account.storage_usage = storage_tracker.finish(account.storage_usage);
+
+        let (token_ids, amounts): (Vec<_>, Vec<_>) = balances
+            .into_iter()
+            .map(|(token_id, amount)| (token_id, U128(amount)))
+            .unzip();
+
+        MtEvent::Burn([MtOwnerTokens {
+            owner_id: account_id,
+            token_ids: &token_ids,
+            amounts: &amounts,
+            memo: Some("unregister"),
+        }])
+        .emit();
+    }
+}
+
+/// Asserts if any approval is provided, transfers on behalf of the owner aren't supported
+fn assert_no_approvals<'a>(approvals: impl IntoIterator<Item = &'a Approval>) {
+    require!(
+        approvals.into_iter().all(Option::is_none),
+        "Approvals are not supported"
+    );
+}
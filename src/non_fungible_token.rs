@@ -0,0 +1,644 @@
+use crate::account::Account;
+use crate::storage::StorageKey;
+use crate::storage_tracker::StorageUsageTrackerData;
+use crate::{Contract, ContractExt};
+use near_contract_standards::non_fungible_token::approval::{
+    ext_nft_approval_receiver, NonFungibleTokenApproval,
+};
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+use near_contract_standards::non_fungible_token::events::{NftBurn, NftMint, NftTransfer};
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata,
+};
+use near_contract_standards::non_fungible_token::{NonFungibleToken, Token, TokenId};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, require, serde_json, AccountId, Gas,
+    Promise, PromiseOrValue, PromiseResult,
+};
+use std::collections::HashMap;
+
+/// Gas reserved for `nft_resolve_transfer` callback
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Gas reserved for `nft_transfer_call` execution apart from `nft_on_transfer` call of the receiver
+const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(30_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+/// Gas reserved for `nft_approve` execution apart from `nft_on_approve` call of the approved account
+const GAS_FOR_NFT_APPROVE: Gas = Gas(10_000_000_000_000);
+
+/// Receiver interface of `nft_transfer_call`, not exported by `near_contract_standards`
+#[allow(dead_code)]
+#[ext_contract(ext_nft_receiver)]
+trait NonFungibleTokenReceiver {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+/// Approved account ids of the token with their approval ids
+type Approvals = HashMap<AccountId, u64>;
+
+/// Non-fungible tokens [NEP-171] state with metadata, enumeration and approval extensions
+///
+/// Storage of a token is charged to its owner's storage balance, see `Contract::track_storage_usage`
+///
+/// [NEP-171]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Core
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct NonFungibleTokens {
+    /// Tokens with their owners, metadata and approvals
+    pub(crate) tokens: NonFungibleToken,
+    /// Contract metadata [NEP-177], set by the owner
+    ///
+    /// [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+    pub(crate) metadata: LazyOption<NFTContractMetadata>,
+}
+
+impl NonFungibleTokens {
+    pub(crate) fn new(owner_id: AccountId) -> Self {
+        Self {
+            tokens: NonFungibleToken::new(
+                StorageKey::NftOwners,
+                owner_id,
+                Some(StorageKey::NftTokenMetadata),
+                Some(StorageKey::NftEnumeration),
+                Some(StorageKey::NftApprovals),
+            ),
+            metadata: LazyOption::new(StorageKey::NftMetadata, None),
+        }
+    }
+
+    fn token_metadata_by_id(&mut self) -> &mut LookupMap<TokenId, TokenMetadata> {
+        self.tokens
+            .token_metadata_by_id
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Metadata extension is not enabled"))
+    }
+
+    fn tokens_per_owner(&mut self) -> &mut LookupMap<AccountId, UnorderedSet<TokenId>> {
+        self.tokens
+            .tokens_per_owner
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Enumeration extension is not enabled"))
+    }
+
+    fn approvals_by_id(&mut self) -> &mut LookupMap<TokenId, Approvals> {
+        self.tokens
+            .approvals_by_id
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Approval extension is not enabled"))
+    }
+
+    fn next_approval_id_by_id(&mut self) -> &mut LookupMap<TokenId, u64> {
+        self.tokens
+            .next_approval_id_by_id
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Approval extension is not enabled"))
+    }
+
+    /// Returns owner of `token_id`
+    fn owner_of(&self, token_id: &TokenId) -> AccountId {
+        self.tokens
+            .owner_by_id
+            .get(token_id)
+            .unwrap_or_else(|| env::panic_str("Token not found"))
+    }
+
+    /// Inserts `token_id` owned by `owner_id` with its `metadata` and next approval id
+    fn insert_token(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        metadata: &TokenMetadata,
+        next_approval_id: Option<u64>,
+    ) {
+        self.tokens.owner_by_id.insert(token_id, owner_id);
+        self.token_metadata_by_id().insert(token_id, metadata);
+
+        if let Some(next_approval_id) = next_approval_id {
+            self.next_approval_id_by_id()
+                .insert(token_id, &next_approval_id);
+        }
+
+        let mut token_ids = self.tokens_per_owner().get(owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::NftTokensPerOwner {
+                account_hash: env::sha256(owner_id.as_bytes()),
+            })
+        });
+        token_ids.insert(token_id);
+        self.tokens_per_owner().insert(owner_id, &token_ids);
+    }
+
+    /// Removes `token_id` owned by `owner_id` and returns its metadata, approvals and next approval id
+    fn remove_token(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+    ) -> (TokenMetadata, Option<Approvals>, Option<u64>) {
+        self.tokens.owner_by_id.remove(token_id);
+
+        let metadata = self
+            .token_metadata_by_id()
+            .remove(token_id)
+            .unwrap_or_else(|| env::panic_str("Token metadata not found"));
+        let approvals = self.approvals_by_id().remove(token_id);
+        let next_approval_id = self.next_approval_id_by_id().remove(token_id);
+
+        let mut token_ids = self
+            .tokens_per_owner()
+            .get(owner_id)
+            .unwrap_or_else(|| env::panic_str("Token not found"));
+        token_ids.remove(token_id);
+
+        if token_ids.is_empty() {
+            self.tokens_per_owner().remove(owner_id);
+        } else {
+            self.tokens_per_owner().insert(owner_id, &token_ids);
+        }
+
+        (metadata, approvals, next_approval_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenCore for Contract {
+    /// Transfers `token_id` to registered `receiver_id`, the caller has to be the owner or approved account
+    ///
+    /// Token storage is released from the owner's storage balance and charged to the receiver's one
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo.as_deref(),
+        );
+    }
+
+    /// Transfers `token_id` to registered `receiver_id` and calls its `nft_on_transfer`
+    ///
+    /// Token is returned to the previous owner in `nft_resolve_transfer` if the receiver asks for it
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        require!(
+            env::prepaid_gas() > GAS_FOR_NFT_TRANSFER_CALL,
+            "More gas is required"
+        );
+
+        let sender_id = env::predecessor_account_id();
+        let (owner_id, approvals) = self.internal_nft_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo.as_deref(),
+        );
+
+        ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL)
+            .nft_on_transfer(sender_id, owner_id.clone(), token_id.clone(), msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(owner_id, receiver_id, token_id, approvals),
+            )
+            .into()
+    }
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.nft.tokens.nft_token(token_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    /// Callback of `nft_transfer_call`, returns whether the token was kept by the receiver
+    ///
+    /// Token isn't returned if the previous owner was unregistered in the meantime, its storage balance
+    /// doesn't cover the token storage anymore or the receiver doesn't own it anymore
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<Approvals>,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+
+        if !must_revert
+            || self.nft.tokens.owner_by_id.get(&token_id).as_ref() != Some(&receiver_id)
+            || self.get_account(&previous_owner_id).is_err()
+        {
+            return true;
+        }
+
+        // approvals set by the receiver are dropped, the previous owner ones are restored
+        let approvals = approved_account_ids.filter(|approvals| !approvals.is_empty());
+        if !self.internal_nft_return(&token_id, &receiver_id, &previous_owner_id, approvals) {
+            return true;
+        }
+
+        NftTransfer {
+            old_owner_id: &receiver_id,
+            new_owner_id: &previous_owner_id,
+            token_ids: &[&token_id],
+            authorized_id: None,
+            memo: None,
+        }
+        .emit();
+
+        false
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    /// Approves `account_id` to transfer `token_id`, storage of the approval is charged to the owner
+    #[payable]
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        let owner_id = self.assert_nft_owner(&token_id);
+
+        let approval_id = self.track_storage_usage(&owner_id, |contract| {
+            let nft = &mut contract.nft;
+
+            let approval_id = nft.next_approval_id_by_id().get(&token_id).unwrap_or(1);
+            nft.next_approval_id_by_id()
+                .insert(&token_id, &(approval_id + 1));
+
+            let mut approvals = nft.approvals_by_id().get(&token_id).unwrap_or_default();
+            approvals.insert(account_id.clone(), approval_id);
+            nft.approvals_by_id().insert(&token_id, &approvals);
+
+            approval_id
+        });
+
+        msg.map(|msg| {
+            ext_nft_approval_receiver::ext(account_id)
+                .with_static_gas(env::prepaid_gas() - GAS_FOR_NFT_APPROVE)
+                .nft_on_approve(token_id, owner_id, approval_id, msg)
+        })
+    }
+
+    /// Revokes approval of `account_id` to transfer `token_id`, its storage is released to the owner
+    #[payable]
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        let owner_id = self.assert_nft_owner(&token_id);
+
+        self.track_storage_usage(&owner_id, |contract| {
+            let approvals_by_id = contract.nft.approvals_by_id();
+
+            if let Some(mut approvals) = approvals_by_id.get(&token_id) {
+                approvals.remove(&account_id);
+
+                if approvals.is_empty() {
+                    approvals_by_id.remove(&token_id);
+                } else {
+                    approvals_by_id.insert(&token_id, &approvals);
+                }
+            }
+        });
+    }
+
+    /// Revokes all approvals of `token_id`, their storage is released to the owner
+    #[payable]
+    fn nft_revoke_all(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        self.assert_contract_running();
+
+        let owner_id = self.assert_nft_owner(&token_id);
+
+        self.track_storage_usage(&owner_id, |contract| {
+            contract.nft.approvals_by_id().remove(&token_id);
+        });
+    }
+
+    fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.nft
+            .tokens
+            .nft_is_approved(token_id, approved_account_id, approval_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenEnumeration for Contract {
+    fn nft_total_supply(&self) -> U128 {
+        self.nft.tokens.nft_total_supply()
+    }
+
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        self.nft.tokens.nft_tokens(from_index, limit)
+    }
+
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        self.nft.tokens.nft_supply_for_owner(account_id)
+    }
+
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        self.nft
+            .tokens
+            .nft_tokens_for_owner(account_id, from_index, limit)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        self.nft
+            .metadata
+            .get()
+            .unwrap_or_else(|| env::panic_str("Contract metadata is not set"))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets contract metadata [NEP-177]
+    ///
+    /// [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+    #[payable]
+    pub fn set_nft_metadata(&mut self, metadata: NFTContractMetadata) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        metadata.assert_valid();
+        self.nft.metadata.set(&metadata);
+    }
+
+    /// Mints `token_id` to registered `token_owner_id`, storage of the token is charged to the token owner
+    #[payable]
+    pub fn nft_mint(
+        &mut self,
+        token_id: TokenId,
+        token_owner_id: AccountId,
+        token_metadata: TokenMetadata,
+    ) -> Token {
+        assert_one_yocto();
+        self.assert_owner();
+
+        token_metadata.assert_valid();
+
+        self.track_storage_usage(&token_owner_id, |contract| {
+            require!(
+                contract.nft.tokens.owner_by_id.get(&token_id).is_none(),
+                "token_id must be unique"
+            );
+
+            contract
+                .nft
+                .insert_token(&token_id, &token_owner_id, &token_metadata, None);
+        });
+
+        let token = Token {
+            token_id,
+            owner_id: token_owner_id,
+            metadata: Some(token_metadata),
+            approved_account_ids: Some(HashMap::new()),
+        };
+
+        NftMint {
+            owner_id: &token.owner_id,
+            token_ids: &[&token.token_id],
+            memo: None,
+        }
+        .emit();
+
+        token
+    }
+}
+
+impl Contract {
+    /// Asserts if the caller isn't the owner of `token_id` or the owner is frozen, returns the owner
+    fn assert_nft_owner(&self, token_id: &TokenId) -> AccountId {
+        let owner_id = self.nft.owner_of(token_id);
+
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Predecessor must be token owner."
+        );
+
+        self.get_account(&owner_id)
+            .unwrap_or_else(|e| env::panic_str(e))
+            .assert_not_frozen();
+
+        owner_id
+    }
+
+    /// Transfers `token_id` to `receiver_id` on behalf of `sender_id`, returns previous owner and approvals
+    // `Option::is_none_or` requires Rust 1.82, wasm of which isn't supported by NEAR runtimes of near-sdk 4.1
+    #[allow(clippy::unnecessary_map_or)]
+    fn internal_nft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<&str>,
+    ) -> (AccountId, Option<Approvals>) {
+        let owner_id = self.nft.owner_of(token_id);
+        let approvals = self.nft.approvals_by_id().get(token_id);
+
+        if sender_id != &owner_id {
+            let actual_approval_id = approvals
+                .as_ref()
+                .and_then(|approvals| approvals.get(sender_id))
+                .unwrap_or_else(|| env::panic_str("Sender not approved"));
+
+            require!(
+                approval_id.map_or(true, |approval_id| &approval_id == actual_approval_id),
+                "The actual approval_id is different from the given approval_id"
+            );
+        }
+
+        require!(
+            &owner_id != receiver_id,
+            "Current and next owner must differ"
+        );
+
+        self.get_account(&owner_id)
+            .unwrap_or_else(|e| env::panic_str(e))
+            .assert_not_frozen();
+
+        // approvals are cleared upon transfer
+        self.internal_nft_move(token_id, &owner_id, receiver_id, None);
+
+        NftTransfer {
+            old_owner_id: &owner_id,
+            new_owner_id: receiver_id,
+            token_ids: &[token_id],
+            authorized_id: Some(sender_id).filter(|sender_id| *sender_id != &owner_id),
+            memo,
+        }
+        .emit();
+
+        (owner_id, approvals)
+    }
+
+    /// Moves `token_id` from `owner_id` to `receiver_id` with provided `approvals`
+    ///
+    /// Token storage is released from the owner's storage balance and charged to the receiver's one
+    fn internal_nft_move(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        receiver_id: &AccountId,
+        approvals: Option<Approvals>,
+    ) {
+        let (metadata, _, next_approval_id) = self.track_storage_usage(owner_id, |contract| {
+            contract.nft.remove_token(token_id, owner_id)
+        });
+
+        self.track_storage_usage(receiver_id, |contract| {
+            contract
+                .nft
+                .insert_token(token_id, receiver_id, &metadata, next_approval_id);
+
+            if let Some(approvals) = approvals {
+                contract.nft.approvals_by_id().insert(token_id, &approvals);
+            }
+        });
+    }
+
+    /// Returns `token_id` from `receiver_id` to `previous_owner_id` with provided `approvals`, never panics on storage
+    ///
+    /// Token stays with the receiver with its approvals if the previous owner's storage balance doesn't cover
+    /// the token storage, e.g. it was withdrawn after the transfer. Returns whether the token was returned
+    fn internal_nft_return(
+        &mut self,
+        token_id: &TokenId,
+        receiver_id: &AccountId,
+        previous_owner_id: &AccountId,
+        approvals: Option<Approvals>,
+    ) -> bool {
+        // releasing storage of the receiver is always covered
+        let (metadata, receiver_approvals, next_approval_id) = self
+            .track_storage_usage(receiver_id, |contract| {
+                contract.nft.remove_token(token_id, receiver_id)
+            });
+
+        let (_, is_covered) = self.try_track_storage_usage(previous_owner_id, |contract| {
+            contract
+                .nft
+                .insert_token(token_id, previous_owner_id, &metadata, next_approval_id);
+
+            if let Some(approvals) = approvals {
+                contract.nft.approvals_by_id().insert(token_id, &approvals);
+            }
+        });
+
+        if is_covered {
+            return true;
+        }
+
+        // storage of the receiver was released above, so restoring the token is covered
+        let (metadata, _, next_approval_id) = self
+            .try_track_storage_usage(previous_owner_id, |contract| {
+                contract.nft.remove_token(token_id, previous_owner_id)
+            })
+            .0;
+
+        self.track_storage_usage(receiver_id, |contract| {
+            contract
+                .nft
+                .insert_token(token_id, receiver_id, &metadata, next_approval_id);
+
+            if let Some(approvals) = receiver_approvals {
+                contract.nft.approvals_by_id().insert(token_id, &approvals);
+            }
+        });
+
+        false
+    }
+
+    /// Returns whether `account_id` owns any token, so it can't be unregistered without `force`
+    pub(crate) fn owns_nft_tokens(&self, account_id: &AccountId) -> bool {
+        self.nft.tokens.nft_supply_for_owner(account_id.clone()).0 > 0
+    }
+
+    /// Burns tokens of the unregistered `account`, their storage is released from its storage usage
+    pub(crate) fn burn_unregistered_nft_tokens(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+    ) {
+        let token_ids = match self.nft.tokens_per_owner().get(account_id) {
+            Some(token_ids) => token_ids.to_vec(),
+            None => return,
+        };
+
+        self.flush(); // flush content before use env::storage_usage()
+        let storage_tracker = StorageUsageTrackerData::default().track();
+
+        for token_id in &token_ids {
+            self.nft.remove_token(token_id, account_id);
+        }
+
+        self.flush();
+        account.storage_usage = storage_tracker.finish(account.storage_usage);
+
+        NftBurn {
+            owner_id: account_id,
+            token_ids: &token_ids.iter().map(String::as_str).collect::<Vec<_>>(),
+            authorized_id: None,
+            memo: Some("unregister"),
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,151 @@
+use crate::events::Event;
+use crate::misc::{MethodGroup, RunningState};
+use crate::roles::Role;
+use crate::{Contract, ContractExt};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId};
+
+#[near_bindgen]
+impl Contract {
+    /// Pauses the contract, blocking all storage management methods
+    #[payable]
+    pub fn pause(&mut self) {
+        assert_one_yocto();
+        self.assert_role(Role::Operator);
+
+        require!(
+            self.running_state == RunningState::Running,
+            "Contract is already paused"
+        );
+
+        self.running_state = RunningState::Paused;
+    }
+
+    /// Pauses the contract in emergency, the caller has to be a guardian
+    ///
+    /// Guardians aren't able to resume the contract, it's done by operators
+    #[payable]
+    pub fn guardian_pause(&mut self) {
+        assert_one_yocto();
+        self.assert_role(Role::Guardian);
+
+        require!(
+            self.running_state == RunningState::Running,
+            "Contract is already paused"
+        );
+
+        self.running_state = RunningState::Paused;
+    }
+
+    /// Resumes previously paused contract
+    #[payable]
+    pub fn resume(&mut self) {
+        assert_one_yocto();
+        self.assert_role(Role::Operator);
+
+        require!(
+            self.running_state == RunningState::Paused,
+            "Contract is not paused"
+        );
+
+        self.running_state = RunningState::Running;
+    }
+
+    /// Returns current contract's state
+    pub fn get_running_state(&self) -> RunningState {
+        self.running_state.clone()
+    }
+
+    /// Pauses `group` methods only, keeping the rest of the contract running
+    #[payable]
+    pub fn pause_methods(&mut self, group: MethodGroup) {
+        assert_one_yocto();
+        self.assert_role(Role::Operator);
+
+        require!(
+            self.paused_methods.insert(group),
+            "Methods are already paused"
+        );
+    }
+
+    /// Resumes previously paused `group` methods
+    #[payable]
+    pub fn resume_methods(&mut self, group: MethodGroup) {
+        assert_one_yocto();
+        self.assert_role(Role::Operator);
+
+        require!(self.paused_methods.remove(group), "Methods are not paused");
+    }
+
+    /// Returns method groups paused separately, regardless of the contract's state
+    pub fn get_paused_methods(&self) -> Vec<MethodGroup> {
+        self.paused_methods.iter().collect()
+    }
+
+    /// Proposes `new_owner_id` as a new owner of the contract
+    ///
+    /// Ownership is transferred only after the proposed account calls `accept_ownership`
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(!self.is_owner(&new_owner_id), "Account is already an owner");
+
+        Event::OwnerProposed {
+            owner_id: &self.owner_id,
+            proposed_owner_id: &new_owner_id,
+        }
+        .emit();
+
+        self.pending_owner_id = Some(new_owner_id);
+    }
+
+    /// Accepts ownership proposed to the caller
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+        assert_one_yocto();
+
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&account_id),
+            "Not a proposed owner"
+        );
+
+        Event::OwnershipTransferred {
+            old_owner_id: &self.owner_id,
+            new_owner_id: &account_id,
+        }
+        .emit();
+
+        self.owner_id = account_id;
+        self.pending_owner_id = None;
+    }
+
+    /// Cancels pending ownership proposal
+    #[payable]
+    pub fn cancel_owner_proposal(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let proposed_owner_id = self
+            .pending_owner_id
+            .take()
+            .unwrap_or_else(|| env::panic_str("No pending owner proposal"));
+
+        Event::OwnerProposalCancelled {
+            owner_id: &self.owner_id,
+            proposed_owner_id: &proposed_owner_id,
+        }
+        .emit();
+    }
+
+    /// Returns the contract's owner account id
+    pub fn owner_id(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Returns account id proposed as a new owner, if any
+    pub fn pending_owner_id(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+}
@@ -0,0 +1,164 @@
+use crate::events::Event;
+use crate::misc::MethodGroup;
+use crate::storage_token::GAS_FOR_FT_TRANSFER;
+use crate::{Contract, ContractExt};
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, AccountId, Balance, Gas, Promise,
+};
+
+/// Gas reserved for the callback, which holds the refund again if transfer to account failed
+const GAS_FOR_ON_REFUND_CLAIM: Gas = Gas(10_000_000_000_000);
+
+#[near_bindgen]
+impl Contract {
+    /// Returns $NEAR held for `account_id`, which failed to be transferred to the account
+    pub fn refund_of(&self, account_id: AccountId) -> U128 {
+        self.refunds
+            .get(&account_id)
+            .copied()
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Transfers $NEAR held for the caller, the refund is held again if the transfer fails
+    #[payable]
+    pub fn claim_refund(&mut self) -> U128 {
+        assert_one_yocto();
+
+        self.assert_methods_running(MethodGroup::Withdrawals);
+
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .refunds
+            .remove(&account_id)
+            .unwrap_or_else(|| env::panic_str("No refund to claim"));
+
+        Event::RefundClaimed {
+            account_id: &account_id,
+            amount: amount.into(),
+        }
+        .emit();
+
+        Promise::new(account_id.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_REFUND_CLAIM)
+                .on_refund_claim(account_id, amount.into()),
+        );
+
+        amount.into()
+    }
+
+    /// Callback of `claim_refund` transfer, holds claimed `amount` again if the transfer failed
+    #[private]
+    pub fn on_refund_claim(&mut self, account_id: AccountId, amount: U128) {
+        if is_promise_success() {
+            return;
+        }
+
+        self.hold_refund(&account_id, amount.0);
+    }
+
+    /// Returns amount of `token_id` tokens held for `account_id`, which failed to be credited back to the account
+    pub fn token_refund_of(&self, account_id: AccountId, token_id: AccountId) -> U128 {
+        self.token_refunds
+            .get(&(account_id, token_id))
+            .copied()
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Transfers `token_id` tokens held for the caller, the refund is held again if the transfer fails
+    #[payable]
+    pub fn claim_token_refund(&mut self, token_id: AccountId) -> U128 {
+        assert_one_yocto();
+
+        self.assert_methods_running(MethodGroup::Withdrawals);
+
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .token_refunds
+            .remove(&(account_id.clone(), token_id.clone()))
+            .unwrap_or_else(|| env::panic_str("No token refund to claim"));
+
+        Event::TokenRefundClaimed {
+            account_id: &account_id,
+            token_id: &token_id,
+            amount: amount.into(),
+        }
+        .emit();
+
+        ext_ft_core::ext(token_id.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(account_id.clone(), amount.into(), None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_REFUND_CLAIM)
+                    .on_token_refund_claim(account_id, token_id, amount.into()),
+            );
+
+        amount.into()
+    }
+
+    /// Callback of `claim_token_refund` transfer, holds claimed `amount` again if the transfer failed
+    #[private]
+    pub fn on_token_refund_claim(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+
+        self.hold_token_refund(&account_id, &token_id, amount.0);
+    }
+}
+
+impl Contract {
+    /// Holds `amount` of $NEAR for `account_id` until it's claimed by `claim_refund`
+    ///
+    /// Used for transfers, which failed when their amount can't be restored to a registered account.
+    /// Refunds aren't registered as accounts, so registration policy can't be bypassed.
+    /// Held $NEAR stays on the contract balance until claimed
+    pub(crate) fn hold_refund(&mut self, account_id: &AccountId, amount: Balance) {
+        let refund = self.refunds.entry(account_id.clone()).or_default();
+        *refund = refund
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Refund overflow"));
+
+        Event::RefundHeld {
+            account_id,
+            amount: amount.into(),
+        }
+        .emit();
+    }
+
+    /// Holds `amount` of `token_id` tokens for `account_id` until it's claimed by `claim_token_refund`
+    ///
+    /// Used for returned tokens, which can't be credited back to the account storage balance
+    pub(crate) fn hold_token_refund(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: Balance,
+    ) {
+        let refund = self
+            .token_refunds
+            .entry((account_id.clone(), token_id.clone()))
+            .or_default();
+        *refund = refund
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Refund overflow"));
+
+        Event::TokenRefundHeld {
+            account_id,
+            token_id,
+            amount: amount.into(),
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,213 @@
+use crate::events::Event;
+use crate::indexed_set::IndexedSet;
+use crate::views::paginate;
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId, Balance, Promise};
+
+/// Policy of new accounts registration
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum RegistrationPolicy {
+    /// Any account is able to register
+    #[default]
+    Open,
+    /// Only accounts from the allow list are able to register
+    AllowList,
+    /// Any account except accounts from the deny list is able to register
+    DenyList,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets policy of new accounts registration, already registered accounts aren't affected
+    #[payable]
+    pub fn set_registration_policy(&mut self, policy: RegistrationPolicy) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        Event::RegistrationPolicyUpdated {
+            old_policy: self.registration_policy,
+            new_policy: policy,
+        }
+        .emit();
+
+        self.registration_policy = policy;
+    }
+
+    /// Adds `account_ids` to the allow list
+    ///
+    /// Storage of the added entries is paid by the attached deposit, unused deposit is refunded to the caller
+    #[payable]
+    pub fn add_to_allow_list(&mut self, account_ids: Vec<AccountId>) {
+        require!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.assert_owner();
+
+        let account_ids = self
+            .charge_list_storage(|contract| insert_accounts(&mut contract.allow_list, account_ids));
+
+        Event::AllowListAdd {
+            account_ids: &account_ids,
+        }
+        .emit();
+    }
+
+    /// Removes `account_ids` from the allow list, storage released by the removed entries is refunded to the caller along with the deposit
+    #[payable]
+    pub fn remove_from_allow_list(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_ids = self
+            .charge_list_storage(|contract| remove_accounts(&mut contract.allow_list, account_ids));
+
+        Event::AllowListRemove {
+            account_ids: &account_ids,
+        }
+        .emit();
+    }
+
+    /// Adds `account_ids` to the deny list
+    ///
+    /// Storage of the added entries is paid by the attached deposit, unused deposit is refunded to the caller
+    #[payable]
+    pub fn add_to_deny_list(&mut self, account_ids: Vec<AccountId>) {
+        require!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.assert_owner();
+
+        let account_ids = self
+            .charge_list_storage(|contract| insert_accounts(&mut contract.deny_list, account_ids));
+
+        Event::DenyListAdd {
+            account_ids: &account_ids,
+        }
+        .emit();
+    }
+
+    /// Removes `account_ids` from the deny list, storage released by the removed entries is refunded to the caller along with the deposit
+    #[payable]
+    pub fn remove_from_deny_list(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_ids = self
+            .charge_list_storage(|contract| remove_accounts(&mut contract.deny_list, account_ids));
+
+        Event::DenyListRemove {
+            account_ids: &account_ids,
+        }
+        .emit();
+    }
+
+    /// Returns current policy of new accounts registration
+    pub fn get_registration_policy(&self) -> RegistrationPolicy {
+        self.registration_policy
+    }
+
+    /// Returns paginated allow list
+    pub fn get_allow_list(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        paginate(&self.allow_list, from_index, limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns paginated deny list
+    pub fn get_deny_list(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        paginate(&self.deny_list, from_index, limit)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Returns whether registration of new `account_id` is allowed by the registration policy
+    pub(crate) fn is_registration_allowed(&self, account_id: &AccountId) -> bool {
+        match self.registration_policy {
+            RegistrationPolicy::Open => true,
+            RegistrationPolicy::AllowList => self.allow_list.contains(account_id),
+            RegistrationPolicy::DenyList => !self.deny_list.contains(account_id),
+        }
+    }
+
+    /// Charges storage added by the list update `f` to the attached deposit and refunds the rest of it
+    /// along with storage released by the update
+    ///
+    /// Panics if the attached deposit doesn't cover the added storage
+    fn charge_list_storage<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.flush(); // flush content before use env::storage_usage()
+        let initial_storage_usage = env::storage_usage();
+
+        let result = f(self);
+
+        self.flush();
+        let storage_usage = env::storage_usage();
+
+        let refund = if storage_usage > initial_storage_usage {
+            let storage_cost =
+                Balance::from(storage_usage - initial_storage_usage) * env::storage_byte_cost();
+            env::attached_deposit()
+                .checked_sub(storage_cost)
+                .unwrap_or_else(|| {
+                    env::panic_str("Not enough deposit to cover storage of the list")
+                })
+        } else {
+            env::attached_deposit()
+                + Balance::from(initial_storage_usage - storage_usage) * env::storage_byte_cost()
+        };
+
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+
+        result
+    }
+
+    /// Asserts if registration of new `account_id` isn't allowed by the registration policy
+    pub(crate) fn assert_registration_allowed(&self, account_id: &AccountId) {
+        require!(
+            self.is_registration_allowed(account_id),
+            "Account is not allowed to register"
+        );
+    }
+}
+
+/// Inserts `account_ids` to the `list` and returns actually inserted ones
+fn insert_accounts(
+    list: &mut IndexedSet<AccountId>,
+    account_ids: Vec<AccountId>,
+) -> Vec<AccountId> {
+    account_ids
+        .into_iter()
+        .filter(|account_id| list.insert(account_id.clone()))
+        .collect()
+}
+
+/// Removes `account_ids` from the `list` and returns actually removed ones
+fn remove_accounts(
+    list: &mut IndexedSet<AccountId>,
+    account_ids: Vec<AccountId>,
+) -> Vec<AccountId> {
+    account_ids
+        .into_iter()
+        .filter(|account_id| list.remove(account_id))
+        .collect()
+}
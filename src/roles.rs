@@ -0,0 +1,158 @@
+use crate::events::Event;
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId};
+
+/// Privileged roles, which could be granted to accounts
+///
+/// The contract's owner is a super-admin and implicitly has all roles
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum Role {
+    /// Grants and revokes operational roles
+    Admin,
+    /// Pauses and resumes the contract
+    Operator,
+    /// Moves collected fees
+    Treasurer,
+    /// Deploys new contract code
+    Upgrader,
+    /// Pauses the contract in emergency, but isn't able to resume it
+    Guardian,
+}
+
+impl Role {
+    /// Returns role which is able to grant and revoke current role
+    ///
+    /// `None` means that the role is managed by the owner only
+    pub(crate) fn admin_role(&self) -> Option<Role> {
+        match self {
+            Role::Admin | Role::Guardian => None,
+            Role::Operator | Role::Treasurer | Role::Upgrader => Some(Role::Admin),
+        }
+    }
+
+    fn flag(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// Set of roles granted to account, stored as a bitset
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub(crate) struct RoleSet(u8);
+
+impl RoleSet {
+    pub(crate) fn contains(&self, role: Role) -> bool {
+        self.0 & role.flag() != 0
+    }
+
+    pub(crate) fn insert(&mut self, role: Role) -> bool {
+        let inserted = !self.contains(role);
+        self.0 |= role.flag();
+        inserted
+    }
+
+    pub(crate) fn remove(&mut self, role: Role) -> bool {
+        let removed = self.contains(role);
+        self.0 &= !role.flag();
+        removed
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants `role` to `account_id`, the caller has to be an admin of the role
+    #[payable]
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_role_admin(role);
+
+        require!(
+            self.roles
+                .entry(account_id.clone())
+                .or_default()
+                .insert(role),
+            "Account already has the role"
+        );
+
+        Event::RoleGranted {
+            role,
+            account_id: &account_id,
+            sender_id: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    /// Revokes `role` from `account_id`, the caller has to be an admin of the role
+    #[payable]
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_role_admin(role);
+
+        self.remove_role(role, &account_id);
+    }
+
+    /// Revokes `role` from the caller
+    #[payable]
+    pub fn renounce_role(&mut self, role: Role) {
+        assert_one_yocto();
+
+        self.remove_role(role, &env::predecessor_account_id());
+    }
+
+    /// Checks if `account_id` has `role`
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.is_owner(&account_id)
+            || self
+                .roles
+                .get(&account_id)
+                .map(|roles| roles.contains(role))
+                .unwrap_or_default()
+    }
+}
+
+impl Contract {
+    /// Asserts if the caller doesn't have `role`
+    pub(crate) fn assert_role(&self, role: Role) {
+        require!(
+            self.has_role(role, env::predecessor_account_id()),
+            "Not allowed"
+        );
+    }
+
+    /// Asserts if the caller is not able to grant and revoke `role`
+    fn assert_role_admin(&self, role: Role) {
+        match role.admin_role() {
+            Some(admin_role) => self.assert_role(admin_role),
+            None => self.assert_owner(),
+        }
+    }
+
+    /// Removes `role` from `account_id` and cleans up the entry if no roles left
+    fn remove_role(&mut self, role: Role, account_id: &AccountId) {
+        let roles = self
+            .roles
+            .get_mut(account_id)
+            .unwrap_or_else(|| env::panic_str("Account doesn't have the role"));
+
+        require!(roles.remove(role), "Account doesn't have the role");
+
+        if roles.is_empty() {
+            self.roles.remove(account_id);
+        }
+
+        Event::RoleRevoked {
+            role,
+            account_id,
+            sender_id: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen};
+
+/// Standards implemented by the contract code, reported in [NEP-330] metadata
+///
+/// [NEP-330]: https://nomicon.io/Standards/SourceMetadata
+const IMPLEMENTED_STANDARDS: &[(&str, &str)] = &[
+    #[cfg(feature = "ft")]
+    ("nep141", "1.0.0"),
+    ("nep145", "1.0.0"),
+    #[cfg(feature = "nft")]
+    ("nep171", "1.1.0"),
+    #[cfg(feature = "nft")]
+    ("nep177", "2.0.0"),
+    #[cfg(feature = "nft")]
+    ("nep178", "1.0.0"),
+    #[cfg(feature = "nft")]
+    ("nep181", "1.0.0"),
+    #[cfg(feature = "mt")]
+    ("nep245", "1.0.0"),
+    #[cfg(feature = "ft")]
+    ("nep148", "1.0.0"),
+    ("nep297", "1.0.0"),
+    ("nep330", "1.1.0"),
+];
+
+/// Contract source metadata [NEP-330]
+///
+/// [NEP-330]: https://nomicon.io/Standards/SourceMetadata
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractSourceMetadata {
+    /// Version of the contract code, taken from `Cargo.toml` at build time
+    pub version: Option<String>,
+    /// Link to the contract source code
+    pub link: Option<String>,
+    /// Standards implemented by the contract
+    pub standards: Vec<Standard>,
+}
+
+/// Implemented standard name and version
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Standard {
+    pub standard: String,
+    pub version: String,
+}
+
+impl ContractSourceMetadata {
+    /// Creates metadata of the current contract code with optionally provided source `link`
+    pub(crate) fn new(link: Option<String>) -> Self {
+        Self {
+            version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+            link,
+            standards: IMPLEMENTED_STANDARDS
+                .iter()
+                .map(|(standard, version)| Standard {
+                    standard: (*standard).to_owned(),
+                    version: (*version).to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns contract source metadata [NEP-330]
+    ///
+    /// [NEP-330]: https://nomicon.io/Standards/SourceMetadata
+    pub fn contract_source_metadata(&self) -> ContractSourceMetadata {
+        self.source_metadata.clone()
+    }
+
+    /// Updates contract source metadata with version and standards of the deployed code
+    ///
+    /// Source link is replaced if `link` is provided, otherwise the current one is kept
+    #[payable]
+    pub fn update_contract_source_metadata(&mut self, link: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let link = link.or_else(|| self.source_metadata.link.take());
+        self.source_metadata = ContractSourceMetadata::new(link);
+    }
+}
@@ -3,6 +3,7 @@ use near_sdk::{BorshStorageKey, StorageUsage};
 
 pub(crate) const U128_STORAGE: StorageUsage = 16;
 pub(crate) const U64_STORAGE: StorageUsage = 8;
+pub(crate) const U32_STORAGE: StorageUsage = 4;
 pub(crate) const U8_STORAGE: StorageUsage = 1;
 
 /// Max length of account id [64 bytes]
@@ -23,11 +24,30 @@ pub(crate) const STORAGE_ENTRY: StorageUsage = 5 * U64_STORAGE;
 /// enum::StorageKey size [1 byte]
 const ENUM_STORAGE_KEY: StorageUsage = U8_STORAGE;
 
-/// Current user account struct size
+/// Legacy (V1) user account struct size
 ///
 /// - storage_balance: [U128_STORAGE]
 /// - storage_usage: [U64_STORAGE]
-pub(crate) const ACCOUNT_STORAGE: StorageUsage = U128_STORAGE + U64_STORAGE;
+pub(crate) const ACCOUNT_V1_STORAGE: StorageUsage = U128_STORAGE + U64_STORAGE;
+
+/// Current user account struct size without sponsor and token credit
+///
+/// - storage_balance: [U128_STORAGE]
+/// - storage_usage: [U64_STORAGE]
+/// - registered_at: [U64_STORAGE]
+/// - sponsor_id: `None` option variant [U8_STORAGE], sponsor id adds [U32_STORAGE] + id length
+/// - sponsored_balance: [U128_STORAGE]
+/// - frozen: [U8_STORAGE]
+/// - ft_balance: [U128_STORAGE]
+/// - token_credit: `None` option variant [U8_STORAGE], token credit adds `Account::token_credit_storage_usage`
+pub(crate) const ACCOUNT_STORAGE: StorageUsage = U128_STORAGE
+    + U64_STORAGE
+    + U64_STORAGE
+    + U8_STORAGE
+    + U128_STORAGE
+    + U8_STORAGE
+    + U128_STORAGE
+    + U8_STORAGE;
 
 /// Versioned user account size
 ///
@@ -35,13 +55,83 @@ pub(crate) const ACCOUNT_STORAGE: StorageUsage = U128_STORAGE + U64_STORAGE;
 /// - current account variant value [ACCOUNT_STORAGE]
 pub(crate) const VACCOUNT_STORAGE: StorageUsage = U8_STORAGE + ACCOUNT_STORAGE;
 
+/// Collection key suffix, which is appended to the prefix by nested collections [1 byte]
+const COLLECTION_KEY_SUFFIX: StorageUsage = U8_STORAGE;
+
+/// `Contract::account_ids` (IndexedSet<AccountId>) element entry
+///
+/// - key: [ENUM_STORAGE_KEY] + [COLLECTION_KEY_SUFFIX] + element index [U32_STORAGE]
+/// - value: [ACCOUNT_ID_STORAGE]
+const ACCOUNT_IDS_ELEMENT_STORAGE: StorageUsage =
+    STORAGE_ENTRY + ENUM_STORAGE_KEY + COLLECTION_KEY_SUFFIX + U32_STORAGE + ACCOUNT_ID_STORAGE;
+
+/// Sha256 hash size, used as a key by hashed collections [32 bytes]
+const HASH_KEY_STORAGE: StorageUsage = 32;
+
+/// `Contract::account_ids` (IndexedSet<AccountId>) index entry
+///
+/// - key: sha256 of prefix and account id [HASH_KEY_STORAGE]
+/// - value: element index [U32_STORAGE]
+const ACCOUNT_IDS_INDEX_STORAGE: StorageUsage = STORAGE_ENTRY + HASH_KEY_STORAGE + U32_STORAGE;
+
+/// Storage in bytes used by registered user account with maximum id length in `Contract::account_ids`
+pub(crate) const ACCOUNT_IDS_MAX_ID_LENGTH_STORAGE: StorageUsage =
+    ACCOUNT_IDS_ELEMENT_STORAGE + ACCOUNT_IDS_INDEX_STORAGE;
+
+/// Number of times registered account id is stored
+///
+/// - `Contract::accounts` entry key
+/// - `Contract::account_ids` element value
+pub(crate) const ACCOUNT_ID_ENTRIES: StorageUsage = 2;
+
 /// Initial (minimum) storage in bytes used by registered user account with maximum id length (64 symbols)
 ///
 /// - `Contract::accounts` (LookupMap<AccountId, VAccount>) entry [STORAGE_ENTRY] + [ENUM_STORAGE_KEY] + [ACCOUNT_ID_STORAGE] + [VACCOUNT_STORAGE]
-pub(crate) const REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE: StorageUsage =
-    STORAGE_ENTRY + ENUM_STORAGE_KEY + ACCOUNT_ID_STORAGE + VACCOUNT_STORAGE;
+/// - `Contract::account_ids` (IndexedSet<AccountId>) entries [ACCOUNT_IDS_MAX_ID_LENGTH_STORAGE]
+pub(crate) const REGISTERED_MAX_ID_LENGTH_ACCOUNT_INITIAL_STORAGE: StorageUsage = STORAGE_ENTRY
+    + ENUM_STORAGE_KEY
+    + ACCOUNT_ID_STORAGE
+    + VACCOUNT_STORAGE
+    + ACCOUNT_IDS_MAX_ID_LENGTH_STORAGE;
 
+/// Storage prefixes of contract collections
+///
+/// Prefix is the variant index, so variants are declared regardless of enabled features
+/// to keep prefixes the same in every build. New variants are only appended
 #[derive(BorshStorageKey, BorshSerialize)]
 pub(crate) enum StorageKey {
     Accounts,
+    Roles,
+    StagedCode,
+    AccountIds,
+    AllowList,
+    DenyList,
+    StorageTokens,
+    #[cfg_attr(not(feature = "ft"), allow(dead_code))]
+    FtMetadata,
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftOwners,
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftTokenMetadata,
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftEnumeration,
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftTokensPerOwner {
+        account_hash: Vec<u8>,
+    },
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftApprovals,
+    #[cfg_attr(not(feature = "nft"), allow(dead_code))]
+    NftMetadata,
+    #[cfg_attr(not(feature = "mt"), allow(dead_code))]
+    MtBalances,
+    #[cfg_attr(not(feature = "mt"), allow(dead_code))]
+    MtAccountBalances {
+        account_hash: Vec<u8>,
+    },
+    #[cfg_attr(not(feature = "mt"), allow(dead_code))]
+    MtTotalSupply,
+    StateVersion,
+    Refunds,
+    TokenRefunds,
 }
@@ -1,10 +1,54 @@
 use crate::account::Account;
+use crate::events::Event;
+use crate::misc::MethodGroup;
 use crate::{Contract, ContractExt};
 use near_contract_standards::storage_management::{
     StorageBalance, StorageBalanceBounds, StorageManagement,
 };
-use near_sdk::json_types::U128;
-use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Promise};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, require, AccountId, Balance, Gas,
+    Promise,
+};
+use std::collections::HashSet;
+
+/// Gas reserved for the callback, which restores storage balance if transfer to account failed
+const GAS_FOR_ON_STORAGE_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Gas reserved for `storage_deposit_batch` execution apart from registration of accounts
+const GAS_FOR_STORAGE_DEPOSIT_BATCH: Gas = Gas(10_000_000_000_000);
+
+/// Gas required to register a single account by `storage_deposit_batch`
+///
+/// Registration of an account with the longest id under allow list policy burns ~0.36 Tgas in host functions
+/// (`test_storage_deposit_batch_host_gas_per_account`), the sandbox test `test_storage_deposit_batch_gas_per_account`
+/// checks that gas burnt per account including wasm execution keeps at least 2x margin below this value
+pub(crate) const GAS_PER_BATCH_ACCOUNT: Gas = Gas(2_000_000_000_000);
+
+/// Result of a single account deposit in `storage_deposit_batch`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDepositBatchResult {
+    pub account_id: AccountId,
+    /// Whether account was registered by the batch, otherwise it was already registered or isn't allowed to register
+    pub registered: bool,
+    /// Storage balance of the account, `None` if registration of the account isn't allowed by the registration policy
+    pub storage_balance: Option<StorageBalance>,
+}
+
+/// Returns part of `deposit_amount`, which could be added to `storage_balance` without exceeding `max_storage_balance`
+pub(crate) fn acceptable_deposit(
+    max_storage_balance: Option<Balance>,
+    storage_balance: Balance,
+    deposit_amount: Balance,
+) -> Balance {
+    max_storage_balance.map_or(deposit_amount, |max_storage_balance| {
+        max_storage_balance
+            .saturating_sub(storage_balance)
+            .min(deposit_amount)
+    })
+}
 
 #[near_bindgen]
 impl StorageManagement for Contract {
@@ -14,7 +58,7 @@ impl StorageManagement for Contract {
         account_id: Option<AccountId>,
         registration_only: Option<bool>,
     ) -> StorageBalance {
-        self.assert_contract_running();
+        self.assert_methods_running(MethodGroup::Deposits);
 
         let deposit_amount = env::attached_deposit();
         if deposit_amount == 0 {
@@ -24,25 +68,62 @@ impl StorageManagement for Contract {
         let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
         let registration_only = registration_only.unwrap_or(false);
 
-        match self.get_account_mut(&account_id) {
+        require!(
+            self.config.allow_deposit_for_others || account_id == env::predecessor_account_id(),
+            "Deposit for other accounts is not allowed"
+        );
+
+        let max_storage_balance = self.config.max_storage_balance.map(|max| max.0);
+        let config = self.config.clone();
+        let mut fee = 0;
+
+        let storage_balance = match self.get_account_mut(&account_id) {
             // if exists and registration only flag is true, then return deposit to user
             Ok(account) if registration_only => {
                 Promise::new(env::predecessor_account_id()).transfer(deposit_amount);
                 account.storage_balance()
             }
 
-            // if exists then update near_balance
+            // if exists then update near_balance up to the maximum storage balance and refund the excess to user
             Ok(account) => {
+                account.assert_not_frozen();
+
+                let amount = acceptable_deposit(
+                    max_storage_balance,
+                    account.storage_balance,
+                    deposit_amount,
+                );
+
+                // fee is charged on accepted amount, refunded excess isn't charged
+                fee = config.deposit_fee(amount);
+                let amount = amount - fee;
+
                 account.storage_balance = account
                     .storage_balance
-                    .checked_add(deposit_amount)
+                    .checked_add(amount)
                     .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+
+                let refund = deposit_amount - amount - fee;
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+
+                if amount > 0 {
+                    Event::StorageDeposit {
+                        account_id: &account_id,
+                        amount: amount.into(),
+                    }
+                    .emit();
+                }
+
                 account.storage_balance()
             }
 
             // if not exist and registration only then register and refund
             Err(_) if registration_only => {
-                let min_balance = Account::required_deposit(Some(&account_id)).into();
+                self.assert_registration_allowed(&account_id);
+
+                let min_balance = self.config.min_deposit(Some(&account_id));
 
                 let refund = deposit_amount.checked_sub(min_balance).unwrap_or_else(|| {
                     env::panic_str("Not enough minimum deposit to register account")
@@ -55,33 +136,68 @@ impl StorageManagement for Contract {
                 let account = Account::new(&account_id, Some(min_balance));
                 let storage_balance = account.storage_balance();
 
-                self.accounts.insert(account_id.clone(), account.into());
+                self.insert_account(&account_id, account);
+
+                Event::StorageRegister {
+                    account_id: &account_id,
+                    amount: storage_balance.total,
+                }
+                .emit();
 
                 storage_balance
             }
 
-            // else register account with all deposit
+            // else register account with all deposit up to the maximum storage balance and refund the excess to user
             _ => {
-                let account = Account::new(&account_id, Some(deposit_amount));
+                self.assert_registration_allowed(&account_id);
+
+                let min_balance = self.config.min_deposit(Some(&account_id));
+                require!(
+                    deposit_amount >= min_balance,
+                    "Not enough minimum deposit to register account"
+                );
+
+                let amount = acceptable_deposit(max_storage_balance, 0, deposit_amount);
+
+                // minimum deposit required for registration isn't charged
+                fee = config.deposit_fee(amount - min_balance);
+                let refund = deposit_amount - amount;
+                let amount = amount - fee;
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+
+                let account = Account::new(&account_id, Some(amount));
                 let storage_balance = account.storage_balance();
 
-                self.accounts.insert(account_id.clone(), account.into());
+                self.insert_account(&account_id, account);
+
+                Event::StorageRegister {
+                    account_id: &account_id,
+                    amount: storage_balance.total,
+                }
+                .emit();
 
                 storage_balance
             }
-        }
+        };
+
+        self.collect_deposit_fee(&account_id, fee);
+
+        storage_balance
     }
 
     #[payable]
     fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
         assert_one_yocto();
 
-        self.assert_contract_running();
+        self.assert_methods_running(MethodGroup::Withdrawals);
 
         let account_id = env::predecessor_account_id();
         let account = self
             .get_account_mut(&account_id)
             .unwrap_or_else(|e| env::panic_str(e));
+        account.assert_not_frozen();
 
         let available_balance = account.storage_balance().available;
         let withdraw_amount = match amount {
@@ -100,7 +216,19 @@ impl StorageManagement for Contract {
 
         let storage_balance = account.storage_balance();
 
-        Promise::new(account_id).transfer(withdraw_amount);
+        Event::StorageWithdraw {
+            account_id: &account_id,
+            amount: withdraw_amount.into(),
+        }
+        .emit();
+
+        Promise::new(account_id.clone())
+            .transfer(withdraw_amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_STORAGE_TRANSFER)
+                    .on_storage_withdraw(account_id, withdraw_amount.into()),
+            );
 
         storage_balance
     }
@@ -109,7 +237,7 @@ impl StorageManagement for Contract {
     fn storage_unregister(&mut self, force: Option<bool>) -> bool {
         assert_one_yocto();
 
-        self.assert_contract_running();
+        self.assert_methods_running(MethodGroup::Unregister);
 
         let account_id = env::predecessor_account_id();
         let force = force.unwrap_or(false);
@@ -118,32 +246,498 @@ impl StorageManagement for Contract {
             // If account by provided `account_id` not found
             Err(_) => false,
 
-            // If try to unregister a positive balance account without `force` set to `true`
-            Ok(account) if account.storage_balance > 0 && !force => env::panic_str(
-                "Unable to unregister a positive balance account without `force` set to `true`",
+            // If try to unregister an account with positive token balance without `force` set to `true`
+            Ok(account) if account.ft_balance > 0 && !force => env::panic_str(
+                "Unable to unregister an account with positive token balance without `force` set to `true`",
+            ),
+
+            // If try to unregister an account owning non-fungible tokens without `force` set to `true`
+            #[cfg(feature = "nft")]
+            Ok(_) if self.owns_nft_tokens(&account_id) && !force => env::panic_str(
+                "Unable to unregister an account owning non-fungible tokens without `force` set to `true`",
+            ),
+
+            // If try to unregister an account owning multi tokens without `force` set to `true`
+            #[cfg(feature = "mt")]
+            Ok(_) if self.owns_mt_balances(&account_id) && !force => env::panic_str(
+                "Unable to unregister an account owning multi tokens without `force` set to `true`",
             ),
 
-            // Unregister account and transfer all funds
+            // If try to unregister a positive balance account without `force` set to `true`, sponsored and credited balances aren't taken into account
+            Ok(account)
+                if account.storage_balance
+                    > account.sponsored_balance + account.credited_balance()
+                    && !force =>
+            {
+                env::panic_str(
+                    "Unable to unregister a positive balance account without `force` set to `true`",
+                )
+            }
+
+            // Unregister account, return sponsored balance to sponsor and transfer the rest of funds
             Ok(account) => {
-                Promise::new(account_id).transfer(account.storage_balance);
+                account.assert_not_frozen();
+
+                let amount = self
+                    .transfer_unregistered_balance(&account_id, account, account_id.clone())
+                    .into();
+
+                if force {
+                    Event::StorageForceUnregister {
+                        account_id: &account_id,
+                        amount,
+                    }
+                    .emit();
+                } else {
+                    Event::StorageUnregister {
+                        account_id: &account_id,
+                        amount,
+                    }
+                    .emit();
+                }
+
                 true
             }
         }
     }
 
-    /// Returns storage min/max bounds in $NEAR for account with maximum id length
+    /// Returns storage min/max bounds in $NEAR for account with maximum id length, according to contract configuration
     fn storage_balance_bounds(&self) -> StorageBalanceBounds {
         StorageBalanceBounds {
-            min: Account::required_deposit(None),
-            max: None,
+            min: self.config.min_deposit(None).into(),
+            max: self.config.max_storage_balance,
         }
     }
 
     /// Returns storage balance by `account_id` if account is registered, otherwise None
     fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
-        self.assert_contract_running();
+        self.assert_methods_running(MethodGroup::Views);
         self.get_account(&account_id)
             .map(|account| account.storage_balance())
             .ok()
     }
 }
+
+#[near_bindgen]
+impl Contract {
+    /// Callback of `storage_withdraw` transfer, restores withdrawn `amount` if the transfer failed
+    ///
+    /// If account was unregistered in the meantime, `amount` is held for it until claimed by `claim_refund`,
+    /// so the account isn't registered again bypassing registration policy
+    #[private]
+    pub fn on_storage_withdraw(&mut self, account_id: AccountId, amount: U128) {
+        if is_promise_success() {
+            return;
+        }
+
+        match self.get_account_mut(&account_id) {
+            Ok(account) => {
+                account.storage_balance = account
+                    .storage_balance
+                    .checked_add(amount.0)
+                    .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+
+                Event::StorageWithdrawRollback {
+                    account_id: &account_id,
+                    amount,
+                }
+                .emit();
+            }
+            Err(_) => self.hold_refund(&account_id, amount.0),
+        }
+    }
+
+    /// Callback of `storage_unregister` transfer, registers removed account again if the transfer failed
+    ///
+    /// If account was registered again in the meantime, its storage balance is increased by `amount`
+    #[private]
+    pub fn on_storage_unregister(
+        &mut self,
+        account_id: AccountId,
+        amount: U128,
+        storage_usage: U64,
+        registered_at: U64,
+        frozen: bool,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+
+        let account = Account {
+            storage_balance: amount.0,
+            storage_usage: storage_usage.0,
+            registered_at: registered_at.0,
+            sponsor_id: None,
+            sponsored_balance: 0,
+            frozen,
+            ft_balance: 0,
+            token_credit: None,
+        };
+        self.restore_storage_balance(&account_id, account);
+
+        Event::StorageUnregisterRollback {
+            account_id: &account_id,
+            amount,
+        }
+        .emit();
+    }
+
+    /// Registers multiple accounts with a single attached deposit, already registered accounts are skipped
+    ///
+    /// Accounts not allowed to register by the registration policy are skipped as well and get no deposit,
+    /// so a single such account doesn't fail the whole batch
+    ///
+    /// Each account gets minimum required deposit for its id length. If `registration_only` isn't `true`,
+    /// the rest of deposit is split equally between registered accounts up to the maximum storage balance.
+    /// Deposit fee is charged on the split part of each account. Remainder of deposit is refunded to the caller.
+    ///
+    /// Batch size is limited by attached gas
+    #[payable]
+    pub fn storage_deposit_batch(
+        &mut self,
+        account_ids: Vec<AccountId>,
+        registration_only: Option<bool>,
+    ) -> Vec<StorageDepositBatchResult> {
+        self.assert_methods_running(MethodGroup::Deposits);
+
+        let deposit_amount = env::attached_deposit();
+        require!(deposit_amount > 0, "No deposit provided");
+        require!(!account_ids.is_empty(), "No accounts provided");
+
+        let max_batch_size = env::prepaid_gas()
+            .0
+            .saturating_sub(GAS_FOR_STORAGE_DEPOSIT_BATCH.0)
+            / GAS_PER_BATCH_ACCOUNT.0;
+        require!(
+            account_ids.len() as u64 <= max_batch_size,
+            "Batch size exceeds limit of attached gas"
+        );
+
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            self.config.allow_deposit_for_others
+                || account_ids
+                    .iter()
+                    .all(|account_id| account_id == &predecessor_id),
+            "Deposit for other accounts is not allowed"
+        );
+
+        // positions and minimum deposits of accounts to register, duplicates are registered once
+        let mut min_deposits: Vec<(usize, Balance)> = Vec::new();
+        let mut unique_ids = HashSet::with_capacity(account_ids.len());
+        for (index, account_id) in account_ids.iter().enumerate() {
+            if !unique_ids.insert(account_id)
+                || self.accounts.contains_key(account_id)
+                || !self.is_registration_allowed(account_id)
+            {
+                continue;
+            }
+
+            min_deposits.push((index, self.config.min_deposit(Some(account_id))));
+        }
+
+        let total_min_deposit: Balance = min_deposits.iter().map(|(_, deposit)| deposit).sum();
+        let mut remaining = deposit_amount
+            .checked_sub(total_min_deposit)
+            .unwrap_or_else(|| env::panic_str("Not enough minimum deposit to register accounts"));
+
+        // the rest of deposit is split equally, unless registration only
+        let extra_deposit = match registration_only.unwrap_or(false) {
+            false if !min_deposits.is_empty() => remaining / min_deposits.len() as Balance,
+            _ => 0,
+        };
+
+        let max_storage_balance = self.config.max_storage_balance.map(|max| max.0);
+
+        for &(index, min_deposit) in &min_deposits {
+            let account_id = &account_ids[index];
+            let extra_amount = acceptable_deposit(max_storage_balance, min_deposit, extra_deposit);
+            remaining -= extra_amount;
+
+            // minimum deposit required for registration isn't charged
+            let fee = self.config.deposit_fee(extra_amount);
+            let amount = min_deposit + extra_amount - fee;
+
+            self.insert_account(account_id, Account::new(account_id, Some(amount)));
+
+            Event::StorageRegister {
+                account_id,
+                amount: amount.into(),
+            }
+            .emit();
+
+            self.collect_deposit_fee(account_id, fee);
+        }
+
+        if remaining > 0 {
+            Promise::new(predecessor_id).transfer(remaining);
+        }
+
+        let registered: HashSet<usize> = min_deposits.iter().map(|&(index, _)| index).collect();
+
+        account_ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, account_id)| {
+                let storage_balance = self
+                    .get_account(&account_id)
+                    .map(|account| account.storage_balance())
+                    .ok();
+
+                StorageDepositBatchResult {
+                    account_id,
+                    registered: registered.contains(&index),
+                    storage_balance,
+                }
+            })
+            .collect()
+    }
+
+    /// Deposits storage balance for `account_id` on behalf of the caller, registering the account if needed
+    ///
+    /// Sponsored balance isn't withdrawable by the account and is returned to the sponsor upon account unregistration.
+    /// Deposit exceeding the maximum storage balance is refunded to the sponsor.
+    /// Deposit fee is charged on the accepted deposit, except minimum deposit required to register the account.
+    #[payable]
+    pub fn storage_sponsor(&mut self, account_id: AccountId) -> StorageBalance {
+        self.assert_methods_running(MethodGroup::Deposits);
+
+        let deposit_amount = env::attached_deposit();
+        require!(deposit_amount > 0, "No deposit provided");
+
+        let sponsor_id = env::predecessor_account_id();
+        require!(sponsor_id != account_id, "Account can't sponsor itself");
+
+        let (mut account, is_registered) = match self.get_account(&account_id) {
+            Ok(account) => (account, true),
+            Err(_) => (Account::new(&account_id, None), false),
+        };
+        account.assert_not_frozen();
+
+        require!(
+            !matches!(&account.sponsor_id, Some(current_sponsor_id) if current_sponsor_id != &sponsor_id),
+            "Account is sponsored by another account"
+        );
+
+        let max_storage_balance = self.config.max_storage_balance.map(|max| max.0);
+        let amount =
+            acceptable_deposit(max_storage_balance, account.storage_balance, deposit_amount);
+
+        let refund = deposit_amount - amount;
+        if refund > 0 {
+            Promise::new(sponsor_id.clone()).transfer(refund);
+        }
+
+        if account.sponsor_id.is_none() {
+            account.set_sponsor(sponsor_id.clone());
+        }
+
+        // deposit required for registration isn't charged
+        let min_deposit = match is_registered {
+            true => 0,
+            false => self
+                .config
+                .min_deposit(Some(&account_id))
+                .max(account.locked_balance()),
+        };
+        let fee = self.config.deposit_fee(amount.saturating_sub(min_deposit));
+        let amount = amount - fee;
+
+        account.storage_balance = account
+            .storage_balance
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+        account.sponsored_balance += amount;
+
+        if !is_registered {
+            self.assert_registration_allowed(&account_id);
+
+            require!(
+                amount >= min_deposit,
+                "Not enough minimum deposit to register account"
+            );
+        }
+
+        require!(
+            account.storage_balance >= account.locked_balance(),
+            "Not enough storage balance to cover storage usage"
+        );
+
+        let storage_balance = account.storage_balance();
+        self.insert_account(&account_id, account);
+
+        Event::StorageSponsor {
+            sponsor_id: &sponsor_id,
+            account_id: &account_id,
+            amount: amount.into(),
+        }
+        .emit();
+
+        self.collect_deposit_fee(&account_id, fee);
+
+        storage_balance
+    }
+
+    /// Callback of sponsored balance refund, restores sponsored balance of `account_id` if the transfer failed
+    ///
+    /// Account unregistered in the meantime is registered again only if registration policy allows it
+    /// and `amount` covers the minimum deposit without exceeding the maximum storage balance.
+    /// Otherwise, or if the account is sponsored by another account, `amount` is held for the sponsor
+    /// until claimed by `claim_refund`
+    #[private]
+    pub fn on_sponsor_refund(
+        &mut self,
+        sponsor_id: AccountId,
+        account_id: AccountId,
+        amount: U128,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+
+        let restored = match self.get_account_mut(&account_id) {
+            Ok(account) => match &account.sponsor_id {
+                Some(current_sponsor_id) if current_sponsor_id != &sponsor_id => false,
+                _ => {
+                    account.storage_balance = account
+                        .storage_balance
+                        .checked_add(amount.0)
+                        .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+                    if account.sponsor_id.is_none() {
+                        account.set_sponsor(sponsor_id.clone());
+                    }
+                    account.sponsored_balance += amount.0;
+
+                    true
+                }
+            },
+            Err(_) => self.register_sponsored_refund(&sponsor_id, &account_id, amount.0),
+        };
+
+        if !restored {
+            self.hold_refund(&sponsor_id, amount.0);
+            return;
+        }
+
+        Event::StorageSponsorRefundRollback {
+            sponsor_id: &sponsor_id,
+            account_id: &account_id,
+            amount,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Adds storage balance of `account` to registered account, otherwise registers `account`
+    fn restore_storage_balance(&mut self, account_id: &AccountId, account: Account) {
+        match self.get_account_mut(account_id) {
+            Ok(registered) => {
+                registered.storage_balance = registered
+                    .storage_balance
+                    .checked_add(account.storage_balance)
+                    .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+            }
+            Err(_) => self.insert_account(account_id, account),
+        }
+    }
+
+    /// Registers `account_id` sponsored by `sponsor_id` with refunded `amount`, if it passes registration checks
+    ///
+    /// Returns whether the account was registered
+    fn register_sponsored_refund(
+        &mut self,
+        sponsor_id: &AccountId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> bool {
+        let mut account = Account::new(account_id, Some(amount));
+        account.set_sponsor(sponsor_id.clone());
+        account.sponsored_balance = amount;
+
+        let min_deposit = self
+            .config
+            .min_deposit(Some(account_id))
+            .max(account.locked_balance());
+        let max_storage_balance = self.config.max_storage_balance.map(|max| max.0);
+
+        if !self.is_registration_allowed(account_id)
+            || amount < min_deposit
+            || acceptable_deposit(max_storage_balance, 0, amount) < amount
+        {
+            return false;
+        }
+
+        self.insert_account(account_id, account);
+
+        true
+    }
+
+    /// Returns sponsored balance of removed `account` to the sponsor and transfers the rest of its balance to `receiver_id`
+    ///
+    /// Tokens deposited for storage are returned to the account itself.
+    /// Token balances and non-fungible tokens of removed account are burned. Removed account is registered again if the transfer fails.
+    /// Returns amount transferred to `receiver_id`
+    pub(crate) fn transfer_unregistered_balance(
+        &mut self,
+        account_id: &AccountId,
+        mut account: Account,
+        receiver_id: AccountId,
+    ) -> Balance {
+        #[cfg(feature = "ft")]
+        self.burn_unregistered_ft_balance(account_id, &mut account);
+        #[cfg(feature = "nft")]
+        self.burn_unregistered_nft_tokens(account_id, &mut account);
+        #[cfg(feature = "mt")]
+        self.burn_unregistered_mt_balances(account_id, &mut account);
+
+        if let Some(token_credit) = account.take_token_credit() {
+            account.storage_balance -= token_credit.storage_balance.min(account.storage_balance);
+
+            self.transfer_credited_tokens(account_id, token_credit);
+        }
+
+        if let Some((sponsor_id, sponsored_balance)) = account.take_sponsor() {
+            let refund = sponsored_balance.min(account.storage_balance);
+            account.storage_balance -= refund;
+
+            self.refund_sponsor(account_id, sponsor_id, refund);
+        }
+
+        Promise::new(receiver_id)
+            .transfer(account.storage_balance)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_STORAGE_TRANSFER)
+                    .on_storage_unregister(
+                        account_id.clone(),
+                        account.storage_balance.into(),
+                        account.storage_usage.into(),
+                        account.registered_at.into(),
+                        account.frozen,
+                    ),
+            );
+
+        account.storage_balance
+    }
+
+    /// Returns sponsored balance of unregistered `account_id` to the sponsor
+    fn refund_sponsor(&mut self, account_id: &AccountId, sponsor_id: AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        Event::StorageSponsorRefund {
+            sponsor_id: &sponsor_id,
+            account_id,
+            amount: amount.into(),
+        }
+        .emit();
+
+        Promise::new(sponsor_id.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_STORAGE_TRANSFER)
+                .on_sponsor_refund(sponsor_id, account_id.clone(), amount.into()),
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::account::{Account, TokenCredit};
+use crate::events::Event;
+use crate::misc::MethodGroup;
+use crate::storage_management::acceptable_deposit;
+use crate::{Contract, ContractExt};
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, require, AccountId, Balance, Gas,
+    PromiseOrValue,
+};
+use primitive_types::U256;
+
+/// Gas attached to `ft_transfer` call, which returns deposited tokens
+pub(crate) const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Gas reserved for the callback, which restores token credit if transfer to account failed
+const GAS_FOR_ON_TOKEN_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Credits storage balance of `sender_id` for `amount` of whitelisted tokens, registering the account if needed
+    ///
+    /// Another account could be credited by passing its id as `msg`, if deposits for others are allowed.
+    /// Each smallest unit of `amount` is converted at the owner-configured rate up to the maximum storage balance,
+    /// token decimals aren't taken into account. Unused amount is returned to the sender.
+    /// Deposit fee isn't charged, as the treasury holds $NEAR only and credited tokens are returned in kind.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_methods_running(MethodGroup::Deposits);
+
+        let token_id = env::predecessor_account_id();
+        let rate = self
+            .storage_tokens
+            .get(&token_id)
+            .copied()
+            .unwrap_or_else(|| env::panic_str("Token is not accepted for storage deposits"));
+
+        let account_id = match msg.as_str() {
+            "" => sender_id.clone(),
+            msg => msg
+                .parse()
+                .unwrap_or_else(|_| env::panic_str("Invalid account id provided in msg")),
+        };
+
+        require!(
+            self.config.allow_deposit_for_others || account_id == sender_id,
+            "Deposit for other accounts is not allowed"
+        );
+
+        let (mut account, is_registered) = match self.get_account(&account_id) {
+            Ok(account) => (account, true),
+            Err(_) => (Account::new(&account_id, None), false),
+        };
+        account.assert_not_frozen();
+
+        require!(
+            !matches!(&account.token_credit, Some(token_credit) if token_credit.token_id != token_id),
+            "Account storage is credited in another token"
+        );
+
+        let max_storage_balance = self.config.max_storage_balance.map(|max| max.0);
+        let deposit_value = amount
+            .0
+            .checked_mul(rate)
+            .unwrap_or_else(|| env::panic_str("Storage balance overflow"));
+
+        // only smallest units fully fitting under the maximum storage balance are used, the rest is returned
+        let used_amount =
+            acceptable_deposit(max_storage_balance, account.storage_balance, deposit_value) / rate;
+        if used_amount == 0 {
+            return PromiseOrValue::Value(amount);
+        }
+
+        let storage_amount = used_amount * rate;
+        account.add_token_credit(token_id.clone(), used_amount, storage_amount);
+
+        if !is_registered {
+            self.assert_registration_allowed(&account_id);
+
+            require!(
+                storage_amount
+                    >= self
+                        .config
+                        .min_deposit(Some(&account_id))
+                        .max(account.locked_balance()),
+                "Not enough minimum deposit to register account"
+            );
+        }
+
+        require!(
+            account.storage_balance >= account.locked_balance(),
+            "Not enough storage balance to cover storage usage"
+        );
+
+        self.insert_account(&account_id, account);
+
+        Event::StorageTokenDeposit {
+            account_id: &account_id,
+            token_id: &token_id,
+            amount: used_amount.into(),
+            storage_amount: storage_amount.into(),
+        }
+        .emit();
+
+        PromiseOrValue::Value((amount.0 - used_amount).into())
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets `rate` in yoctoNEAR per smallest token unit, at which `token_id` is accepted for storage deposits
+    ///
+    /// Rate applies to raw token amounts, so for a token with 6 decimals and 1 $NEAR per whole token the rate is 10^18.
+    /// `None` rate stops accepting the token, already credited balances aren't affected
+    #[payable]
+    pub fn set_storage_token(&mut self, token_id: AccountId, rate: Option<U128>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        match rate {
+            Some(U128(rate)) => {
+                require!(rate > 0, "Rate should be a positive number");
+                self.storage_tokens.insert(token_id.clone(), rate);
+            }
+            None => {
+                self.storage_tokens.remove(&token_id);
+            }
+        }
+
+        Event::StorageTokenUpdated {
+            token_id: &token_id,
+            rate,
+        }
+        .emit();
+    }
+
+    /// Returns tokens accepted for storage deposits with their rates in yoctoNEAR per smallest token unit
+    pub fn get_storage_tokens(&self) -> Vec<(AccountId, U128)> {
+        self.storage_tokens
+            .iter()
+            .map(|(token_id, rate)| (token_id.clone(), U128(*rate)))
+            .collect()
+    }
+
+    /// Returns `amount` of deposited tokens to the caller, all available tokens if `amount` isn't provided
+    ///
+    /// Storage balance is reduced proportionally to the credit, locked storage balance isn't withdrawable
+    #[payable]
+    pub fn storage_withdraw_token(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+
+        self.assert_methods_running(MethodGroup::Withdrawals);
+
+        let account_id = env::predecessor_account_id();
+        let account = self
+            .get_account_mut(&account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        account.assert_not_frozen();
+
+        let token_credit = account
+            .token_credit
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Account has no token credit"));
+
+        let available_amount = mul_div(
+            account.available_credited_balance(),
+            token_credit.amount,
+            token_credit.storage_balance,
+        );
+        let withdraw_amount = match amount {
+            Some(U128(amount)) if amount > 0 && amount <= available_amount => amount,
+
+            // If amount not provided, use all available tokens
+            None if available_amount > 0 => available_amount,
+
+            _ => env::panic_str("Not enough available tokens to withdraw"),
+        };
+
+        // credited balance is rounded up, so the rest of credit isn't overvalued
+        let storage_amount = mul_div_ceil(
+            withdraw_amount,
+            token_credit.storage_balance,
+            token_credit.amount,
+        );
+        account.remove_token_credit(withdraw_amount, storage_amount);
+
+        let storage_balance = account.storage_balance();
+
+        self.transfer_credited_tokens(
+            &account_id,
+            TokenCredit {
+                token_id: token_credit.token_id,
+                amount: withdraw_amount,
+                storage_balance: storage_amount,
+            },
+        );
+
+        storage_balance
+    }
+
+    /// Callback of deposited tokens transfer, restores token credit of `account_id` if the transfer failed
+    ///
+    /// If account was unregistered in the meantime or its storage is credited in another token,
+    /// tokens are held for it until claimed by `claim_token_refund`, so the account isn't registered again
+    /// bypassing registration checks
+    #[private]
+    pub fn on_storage_token_withdraw(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+        storage_amount: U128,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+
+        match self.get_account_mut(&account_id) {
+            // credit in another token can't be merged
+            Ok(account) if !matches!(&account.token_credit, Some(token_credit) if token_credit.token_id != token_id) =>
+            {
+                account.add_token_credit(token_id.clone(), amount.0, storage_amount.0);
+
+                Event::StorageTokenWithdrawRollback {
+                    account_id: &account_id,
+                    token_id: &token_id,
+                    amount,
+                    storage_amount,
+                }
+                .emit();
+            }
+            _ => self.hold_token_refund(&account_id, &token_id, amount.0),
+        }
+    }
+}
+
+impl Contract {
+    /// Transfers tokens of removed `token_credit` to `account_id`, the credit is restored if the transfer fails
+    pub(crate) fn transfer_credited_tokens(
+        &mut self,
+        account_id: &AccountId,
+        token_credit: TokenCredit,
+    ) {
+        Event::StorageTokenWithdraw {
+            account_id,
+            token_id: &token_credit.token_id,
+            amount: token_credit.amount.into(),
+            storage_amount: token_credit.storage_balance.into(),
+        }
+        .emit();
+
+        ext_ft_core::ext(token_credit.token_id.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(account_id.clone(), token_credit.amount.into(), None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_TOKEN_TRANSFER)
+                    .on_storage_token_withdraw(
+                        account_id.clone(),
+                        token_credit.token_id,
+                        token_credit.amount.into(),
+                        token_credit.storage_balance.into(),
+                    ),
+            );
+    }
+}
+
+/// Returns `a * b / c` rounded down
+fn mul_div(a: Balance, b: Balance, c: Balance) -> Balance {
+    (U256::from(a) * U256::from(b) / U256::from(c)).as_u128()
+}
+
+/// Returns `a * b / c` rounded up
+fn mul_div_ceil(a: Balance, b: Balance, c: Balance) -> Balance {
+    let c = U256::from(c);
+    ((U256::from(a) * U256::from(b) + c - 1) / c).as_u128()
+}
@@ -1,9 +1,10 @@
 use std::cmp::Ordering;
 
-use near_sdk::{env, require, StorageUsage};
+use crate::Contract;
+use near_sdk::{env, require, AccountId, StorageUsage};
 
 #[derive(Default)]
-pub(crate) struct StorageUsageTrackerData {
+pub struct StorageUsageTrackerData {
     is_tracked: bool,
     pub(crate) last_storage_usage: StorageUsage,
 }
@@ -43,3 +44,77 @@ impl StorageUsageTrackerData {
         require!(self.is_tracked, "Storage tracking is not enabled")
     }
 }
+
+impl Contract {
+    /// Tracks storage usage changes made by `f` and charges them to `account_id`
+    ///
+    /// Panics if the account storage balance doesn't cover its storage usage afterwards
+    pub fn track_storage_usage<R>(
+        &mut self,
+        account_id: &AccountId,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let storage_tracker = self.start_storage_tracking(account_id);
+        let result = f(self);
+        self.finish_storage_tracking(account_id, storage_tracker);
+        result
+    }
+
+    /// Tracks storage usage changes made by `f` and charges them to `account_id` without panicking
+    ///
+    /// Returns result of `f` and whether the account storage balance covers its storage usage afterwards,
+    /// the caller has to revert changes made by `f` if it doesn't
+    pub fn try_track_storage_usage<R>(
+        &mut self,
+        account_id: &AccountId,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> (R, bool) {
+        let storage_tracker = self.start_storage_tracking(account_id);
+        let result = f(self);
+        let is_covered = self.try_finish_storage_tracking(account_id, storage_tracker);
+        (result, is_covered)
+    }
+
+    /// Starts tracking storage usage changes, which would be charged to `account_id`
+    pub fn start_storage_tracking(&mut self, account_id: &AccountId) -> StorageUsageTrackerData {
+        // upgrade legacy account data beforehand, its size change is already accounted by upgrade
+        self.get_account_mut(account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        self.flush(); // flush content before use env::storage_usage()
+
+        StorageUsageTrackerData::default().track()
+    }
+
+    /// Finishes tracking storage usage changes and charges them to `account_id`
+    ///
+    /// Panics if the account storage balance doesn't cover its storage usage
+    pub fn finish_storage_tracking(
+        &mut self,
+        account_id: &AccountId,
+        storage_tracker: StorageUsageTrackerData,
+    ) {
+        require!(
+            self.try_finish_storage_tracking(account_id, storage_tracker),
+            "Not enough storage balance to cover storage usage"
+        );
+    }
+
+    /// Finishes tracking storage usage changes and charges them to `account_id`
+    ///
+    /// Returns whether the account storage balance covers its storage usage
+    fn try_finish_storage_tracking(
+        &mut self,
+        account_id: &AccountId,
+        storage_tracker: StorageUsageTrackerData,
+    ) -> bool {
+        self.flush(); // flush content before use env::storage_usage()
+
+        let account = self
+            .get_account_mut(account_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+
+        account.storage_usage = storage_tracker.finish(account.storage_usage);
+
+        account.storage_balance >= account.locked_balance()
+    }
+}
@@ -0,0 +1,197 @@
+use super::super::account::{Account, VAccount};
+use super::super::Contract;
+use super::common::*;
+use crate::storage::{StorageKey, ACCOUNT_STORAGE, ACCOUNT_V1_STORAGE};
+use near_contract_standards::storage_management::*;
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::test_utils::accounts;
+use near_sdk::{env, testing_env, AccountId, Balance, StorageUsage, ONE_NEAR};
+
+/// Returns `Contract::accounts` storage key of the provided `account_id`
+fn account_key(account_id: &AccountId) -> Vec<u8> {
+    [
+        StorageKey::Accounts.try_to_vec().unwrap(),
+        account_id.try_to_vec().unwrap(),
+    ]
+    .concat()
+}
+
+/// Storage usage growth of legacy account upon upgrade to current version
+fn upgrade_storage_usage(account_id: &AccountId) -> StorageUsage {
+    ACCOUNT_STORAGE - ACCOUNT_V1_STORAGE + Account::account_ids_storage_usage(account_id)
+}
+
+/// Writes legacy `VAccount::V1` Borsh bytes directly to the storage
+fn write_v1_account(account_id: &AccountId, storage_balance: Balance) -> StorageUsage {
+    let storage_usage =
+        Account::initial_storage_usage(Some(account_id)) - upgrade_storage_usage(account_id);
+
+    let mut value = vec![0u8]; // `VAccount::V1` variant index
+    value.extend(storage_balance.to_le_bytes());
+    value.extend(storage_usage.to_le_bytes());
+
+    env::storage_write(&account_key(account_id), &value);
+
+    storage_usage
+}
+
+#[test]
+fn test_read_v1_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let contract = Contract::init(Some(accounts(0)), None, None);
+    let legacy_storage_usage = write_v1_account(&account_id, ONE_NEAR);
+
+    assert!(matches!(
+        contract.accounts.get(&account_id),
+        Some(VAccount::V1(_))
+    ));
+
+    let account = contract.get_account(&account_id).unwrap();
+
+    assert_eq!(account.storage_balance, ONE_NEAR);
+    assert_eq!(
+        account.storage_usage,
+        legacy_storage_usage + upgrade_storage_usage(&account_id)
+    );
+    assert_eq!(account.registered_at, 0);
+
+    // read access doesn't rewrite legacy entry
+    assert_eq!(
+        env::storage_read(&account_key(&account_id)).unwrap()[0],
+        0,
+        "Legacy entry must not be upgraded on read access"
+    );
+}
+
+#[test]
+fn test_upgrade_v1_account_on_write_access() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    let legacy_storage_usage = write_v1_account(&account_id, ONE_NEAR);
+
+    let storage_usage_before = env::storage_usage();
+
+    let account = contract.get_account_mut(&account_id).unwrap();
+    assert_eq!(
+        account.storage_usage,
+        legacy_storage_usage + upgrade_storage_usage(&account_id)
+    );
+
+    contract.flush(); // flush content before use env::storage_usage()
+
+    // entry is rewritten as current version, account is indexed and the storage change is accounted
+    let value = env::storage_read(&account_key(&account_id)).unwrap();
+    assert_eq!(value[0], 1);
+    assert!(contract.account_ids.contains(&account_id));
+    assert_eq!(
+        env::storage_usage() - storage_usage_before,
+        upgrade_storage_usage(&account_id)
+    );
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id))
+    );
+}
+
+#[test]
+fn test_storage_deposit_upgrades_v1_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    write_v1_account(&account_id, ONE_NEAR);
+
+    let storage_balance = contract.storage_deposit(None, None);
+    contract.flush(); // flush content before use env::storage_usage()
+
+    assert_eq!(storage_balance.total.0, 2 * ONE_NEAR);
+    assert!(matches!(
+        contract.accounts.get(&account_id),
+        Some(VAccount::Current(_))
+    ));
+    assert_eq!(
+        storage_balance.available.0,
+        2 * ONE_NEAR - Account::required_deposit(Some(&account_id)).0
+    );
+}
+
+#[test]
+fn test_storage_unregister_v1_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    write_v1_account(&account_id, ONE_NEAR);
+
+    assert!(contract.storage_unregister(Some(true)));
+    contract.flush(); // flush content before use env::storage_usage()
+
+    assert!(env::storage_read(&account_key(&account_id)).is_none());
+}
+
+#[test]
+fn test_index_legacy_accounts() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    write_v1_account(&accounts(1), ONE_NEAR);
+    write_v1_account(&accounts(2), ONE_NEAR);
+    assert_eq!(contract.accounts_count(), 0);
+
+    // unregistered accounts are skipped
+    let indexed = contract.index_legacy_accounts(vec![accounts(1), accounts(3)]);
+
+    assert_eq!(indexed, 1);
+    assert_eq!(contract.accounts_count(), 1);
+
+    // already indexed accounts are skipped
+    let indexed = contract.index_legacy_accounts(vec![accounts(1), accounts(2)]);
+
+    assert_eq!(indexed, 1);
+    assert_eq!(contract.accounts_count(), 2);
+    assert_eq!(
+        contract
+            .get_accounts(None, None)
+            .into_iter()
+            .map(|account| account.account_id)
+            .collect::<Vec<_>>(),
+        vec![accounts(1), accounts(2)]
+    );
+}
+
+#[test]
+fn test_register_current_account() {
+    let account_id = accounts(1);
+
+    let mut context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.block_timestamp(42).build());
+    contract.storage_deposit(None, None);
+    contract.flush(); // flush content before use env::storage_usage()
+
+    let value = env::storage_read(&account_key(&account_id)).unwrap();
+
+    assert_eq!(value[0], 1);
+    assert_eq!(value.len() as StorageUsage, 1 + ACCOUNT_STORAGE);
+    assert_eq!(contract.get_account(&account_id).unwrap().registered_at, 42);
+}
@@ -1,2 +1,4 @@
+mod owner_test;
 mod storage_management_test;
+mod upgrade_test;
 mod utils;
@@ -0,0 +1,103 @@
+use super::utils::{build_contract, gen_user_account};
+use crate::misc::RunningState;
+use near_sdk::serde_json::json;
+use near_sdk::{ONE_NEAR, ONE_YOCTO};
+use workspaces::{Account, Contract};
+
+#[tokio::test]
+async fn test_pause_blocks_storage_management() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = build_contract(&worker, "./").await?;
+
+    // generate sub-account and transfer funds
+    let user1 = gen_user_account(&worker, "user1.test.near").await?;
+
+    // register user before pausing, so withdraw and unregister have something to work with
+    user1
+        .call(contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    // contract is initialized with its own account as an owner
+    let owner = contract.as_account();
+
+    // only owner is able to pause contract
+    let res = call_owner_method(&contract, &user1, "pause").await?;
+    assert!(format!("{:?}", res.into_result()).contains("Not allowed"));
+
+    call_owner_method(&contract, owner, "pause")
+        .await?
+        .into_result()?;
+    assert_eq!(get_running_state(&contract).await?, RunningState::Paused);
+
+    let res = user1
+        .call(contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", res.into_result()).contains("Contract paused"));
+
+    let res = user1
+        .call(contract.id(), "storage_withdraw")
+        .args_json(json!({}))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", res.into_result()).contains("Contract paused"));
+
+    let res = user1
+        .call(contract.id(), "storage_unregister")
+        .args_json(json!({ "force": true }))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", res.into_result()).contains("Contract paused"));
+
+    // resumed contract accepts storage management calls again
+    call_owner_method(&contract, owner, "resume")
+        .await?
+        .into_result()?;
+    assert_eq!(get_running_state(&contract).await?, RunningState::Running);
+
+    user1
+        .call(contract.id(), "storage_withdraw")
+        .args_json(json!({}))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+async fn call_owner_method(
+    contract: &Contract,
+    caller: &Account,
+    method: &str,
+) -> anyhow::Result<workspaces::result::ExecutionFinalResult> {
+    Ok(caller
+        .call(contract.id(), method)
+        .args_json(json!({}))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+async fn get_running_state(contract: &Contract) -> anyhow::Result<RunningState> {
+    contract
+        .view("get_running_state")
+        .args_json(json!({}))
+        .await?
+        .json::<RunningState>()
+        .map_err(|e| anyhow::Error::msg(format!("Parse `RunningState` failed. {:?}", e)))
+}
@@ -5,7 +5,8 @@ use near_contract_standards::storage_management::StorageBalance;
 use near_sdk::json_types::U128;
 use near_sdk::serde_json::json;
 use near_sdk::{ONE_NEAR, ONE_YOCTO};
-use workspaces::{Account, Contract};
+use workspaces::types::{AccessKey, KeyType, SecretKey};
+use workspaces::{Account, Contract, DevNetwork, Worker};
 
 #[tokio::test]
 async fn test_user_registration_and_withdraw() -> anyhow::Result<()> {
@@ -62,6 +63,135 @@ async fn test_user_registration_only() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_storage_withdraw_to_deleted_account_rollback() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = build_contract(&worker, "./").await?;
+
+    // generate sub-account and transfer funds
+    let user1 = gen_user_account(&worker, "user1.test.near").await?;
+    let user1_deleter = add_full_access_key(&worker, &user1).await?;
+
+    let deposit = 3 * ONE_NEAR;
+    storage_deposit(&contract, &user1, deposit, None).await?;
+
+    // receiver account is deleted before the withdrawn amount is transferred to it
+    let withdraw = user1
+        .call(contract.id(), "storage_withdraw")
+        .args_json(json!({
+            "amount": U128(ONE_NEAR),
+        }))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact_async()
+        .await?;
+    user1_deleter
+        .delete_account(contract.id())
+        .await?
+        .into_result()?;
+
+    let res = withdraw.wait().await?;
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.contains(r#""event":"storage_withdraw_rollback""#)));
+
+    // withdrawn amount is returned to account storage balance
+    let user1_storage_balance = storage_balance_of(&contract, &user1).await?;
+    assert_eq!(user1_storage_balance.map(|b| b.total.0), Some(deposit));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_unregister_to_deleted_account_rollback() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = build_contract(&worker, "./").await?;
+
+    // generate sub-account and transfer funds
+    let user1 = gen_user_account(&worker, "user1.test.near").await?;
+    let user1_deleter = add_full_access_key(&worker, &user1).await?;
+
+    let deposit = 3 * ONE_NEAR;
+    storage_deposit(&contract, &user1, deposit, None).await?;
+
+    // receiver account is deleted before the storage balance is transferred to it
+    let unregister = user1
+        .call(contract.id(), "storage_unregister")
+        .args_json(json!({
+            "force": true,
+        }))
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact_async()
+        .await?;
+    user1_deleter
+        .delete_account(contract.id())
+        .await?
+        .into_result()?;
+
+    let res = unregister.wait().await?;
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.contains(r#""event":"storage_unregister_rollback""#)));
+
+    // account is registered again with its storage balance
+    let user1_storage_balance = storage_balance_of(&contract, &user1).await?;
+    assert_eq!(user1_storage_balance.map(|b| b.total.0), Some(deposit));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_deposit_batch_gas_per_account() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = build_contract(&worker, "./").await?;
+
+    let batch_gas = |offset: u64, size: u64| {
+        let account_ids: Vec<String> = (offset..offset + size)
+            .map(|index| format!("{:0>59}.near", index))
+            .collect();
+
+        contract
+            .as_account()
+            .call(contract.id(), "storage_deposit_batch")
+            .args_json(json!({ "account_ids": account_ids }))
+            .deposit(100 * ONE_NEAR)
+            .max_gas()
+            .transact()
+    };
+
+    let single = batch_gas(0, 1).await?.into_result()?.total_gas_burnt;
+    let batch = batch_gas(1, 11).await?.into_result()?.total_gas_burnt;
+    let gas_per_account = (batch - single) / 10;
+
+    assert!(
+        2 * gas_per_account <= crate::storage_management::GAS_PER_BATCH_ACCOUNT.0,
+        "Gas per account {} leaves no margin",
+        gas_per_account
+    );
+
+    Ok(())
+}
+
+// Add another full access key to the account, so it could send transactions concurrently
+async fn add_full_access_key<T>(worker: &Worker<T>, account: &Account) -> anyhow::Result<Account>
+where
+    T: DevNetwork + Send + Sync,
+{
+    let sk = SecretKey::from_random(KeyType::ED25519);
+
+    account
+        .batch(account.id())
+        .add_key(sk.public_key(), AccessKey::full_access())
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(Account::from_secret_key(account.id().clone(), sk, worker))
+}
+
 async fn storage_deposit(
     contract: &Contract,
     target_account: &Account,
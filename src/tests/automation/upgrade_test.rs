@@ -0,0 +1,138 @@
+use std::str::FromStr;
+
+use super::utils::{build_contract, gen_user_account};
+use crate::account::{Account, AccountV1, VAccount};
+use crate::misc::RunningState;
+use crate::storage::{StorageKey, ACCOUNT_STORAGE, ACCOUNT_V1_STORAGE};
+use crate::upgrade::OldContract;
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::serde_json::json;
+use near_sdk::store::LookupMap;
+use near_sdk::{ONE_NEAR, ONE_YOCTO};
+use workspaces::operations::Function;
+
+/// Gas attached to `migrate` call [100 TGas]
+const MIGRATE_GAS: u64 = 100_000_000_000_000;
+
+#[tokio::test]
+async fn test_upgrade_from_previous_layout() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let wasm = workspaces::compile_project("./").await?;
+
+    // contract isn't initialized, so its state contains nothing but the previous release layout
+    let contract = worker.dev_deploy(&wasm).await?;
+
+    let account_id = near_sdk::AccountId::from_str("user1.test.near")?;
+    let account = VAccount::V1(AccountV1 {
+        storage_balance: ONE_NEAR,
+        storage_usage: Account::initial_storage_usage(Some(&account_id))
+            - (ACCOUNT_STORAGE - ACCOUNT_V1_STORAGE)
+            - Account::account_ids_storage_usage(&account_id),
+    });
+    let account_key = [StorageKey::Accounts.try_to_vec()?, account_id.try_to_vec()?].concat();
+
+    let old_state = OldContract {
+        owner_id: near_sdk::AccountId::from_str(contract.id().as_str())?,
+        running_state: RunningState::Running,
+        accounts: LookupMap::new(StorageKey::Accounts),
+    };
+    worker
+        .patch_state(contract.id(), b"STATE", &old_state.try_to_vec()?)
+        .await?;
+    worker
+        .patch_state(contract.id(), &account_key, &account.try_to_vec()?)
+        .await?;
+
+    // the previous release has no upgrade methods, so its code is replaced with the account key
+    contract
+        .as_account()
+        .batch(contract.id())
+        .deploy(&wasm)
+        .call(
+            Function::new("migrate")
+                .args_json(json!({}))
+                .gas(MIGRATE_GAS),
+        )
+        .transact()
+        .await?
+        .into_result()?;
+
+    // new state layout fields are initialized
+    let owner_id = contract
+        .view("owner_id")
+        .args_json(json!({}))
+        .await?
+        .json::<near_sdk::AccountId>()?;
+    assert_eq!(owner_id.as_str(), contract.id().as_str());
+
+    let pending_owner_id = contract
+        .view("pending_owner_id")
+        .args_json(json!({}))
+        .await?
+        .json::<Option<near_sdk::AccountId>>()?;
+    assert_eq!(pending_owner_id, None);
+
+    // registered accounts survive upgrade
+    let storage_balance = contract
+        .view("storage_balance_of")
+        .args_json(json!({
+            "account_id": account_id,
+        }))
+        .await?
+        .json::<Option<StorageBalance>>()?;
+    assert_eq!(
+        storage_balance.map(|balance| balance.total.0),
+        Some(ONE_NEAR)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_entrypoint_stages_code() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = build_contract(&worker, "./").await?;
+    let wasm = workspaces::compile_project("./").await?;
+
+    let user1 = gen_user_account(&worker, "user1.test.near").await?;
+
+    // only owner or upgrader is able to stage code
+    let res = user1
+        .call(contract.id(), "upgrade")
+        .args(wasm.clone())
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", res.into_result()).contains("Not allowed"));
+
+    contract
+        .as_account()
+        .call(contract.id(), "upgrade")
+        .args(wasm)
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    let staged_code_info = contract
+        .view("staged_code_info")
+        .args_json(json!({}))
+        .await?
+        .json::<Option<near_sdk::serde_json::Value>>()?;
+    assert!(staged_code_info.is_some());
+
+    // staged code isn't deployed before the upgrade delay
+    let res = contract
+        .as_account()
+        .call(contract.id(), "deploy_staged_code")
+        .deposit(ONE_YOCTO)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", res.into_result()).contains("Staged code is locked"));
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
-use near_sdk::{test_utils::VMContextBuilder, AccountId, Balance, Gas};
+use near_sdk::mock::VmAction;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::test_utils::{get_created_receipts, get_logs};
+use near_sdk::{test_utils::VMContextBuilder, AccountId, Balance, Gas, VMContext};
 
 pub(crate) const MAX_GAS: Gas = Gas(300_000_000_000_000);
 
+/// Prefix of NEP-297 event logs
+const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
 pub(crate) fn build_default_context(
     predecessor_account_id: AccountId,
     deposit: Option<Balance>,
@@ -15,3 +21,36 @@ pub(crate) fn build_default_context(
         .attached_deposit(deposit.unwrap_or_default());
     builder
 }
+
+/// Returns context of the call with raw `input`
+pub(crate) fn with_input(context: &VMContextBuilder, input: &[u8]) -> VMContext {
+    let mut context = context.build();
+    context.input = input.to_vec();
+    context
+}
+
+/// Returns parsed JSON of NEP-297 events logged by the current call
+pub(crate) fn get_events() -> Vec<Value> {
+    get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix(EVENT_JSON_PREFIX))
+        .map(|json| serde_json::from_str(json).unwrap())
+        .collect()
+}
+
+/// Returns transferred amounts of created receipts by receiver account id
+pub(crate) fn get_transfers() -> Vec<(AccountId, Balance)> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| {
+            let receiver_id = receipt.receiver_id;
+            receipt
+                .actions
+                .into_iter()
+                .filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+        })
+        .collect()
+}
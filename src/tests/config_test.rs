@@ -0,0 +1,171 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use crate::config::{Config, MAX_DEPOSIT_FEE_BPS};
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::accounts;
+use near_sdk::{testing_env, ONE_NEAR};
+
+fn config() -> Config {
+    Config {
+        min_storage_deposit: Some(U128(ONE_NEAR)),
+        max_storage_balance: Some(U128(3 * ONE_NEAR)),
+        allow_deposit_for_others: false,
+        deposit_fee_bps: 100,
+    }
+}
+
+#[test]
+fn test_init_with_config() {
+    let contract = Contract::init(Some(accounts(0)), None, Some(config()));
+
+    assert_eq!(contract.get_config(), config());
+
+    let bounds = contract.storage_balance_bounds();
+    assert_eq!(bounds.min.0, ONE_NEAR);
+    assert_eq!(bounds.max, Some(U128(3 * ONE_NEAR)));
+}
+
+#[test]
+fn test_default_config_bounds() {
+    let contract = Contract::init(Some(accounts(0)), None, None);
+
+    assert_eq!(contract.get_config(), Config::default());
+
+    let bounds = contract.storage_balance_bounds();
+    assert_eq!(bounds.min, Account::required_deposit(None));
+    assert_eq!(bounds.max, None);
+}
+
+#[test]
+fn test_min_storage_deposit_below_storage_cost() {
+    let config = Config {
+        min_storage_deposit: Some(U128(1)),
+        ..Config::default()
+    };
+    let contract = Contract::init(Some(accounts(0)), None, Some(config));
+
+    assert_eq!(
+        contract.storage_balance_bounds().min,
+        Account::required_deposit(None)
+    );
+}
+
+#[test]
+fn test_update_config() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.update_config(config());
+
+    assert_eq!(contract.get_config(), config());
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "config_updated",
+            "data": {
+                "old_config": {
+                    "min_storage_deposit": null,
+                    "max_storage_balance": null,
+                    "allow_deposit_for_others": true,
+                    "deposit_fee_bps": 0,
+                },
+                "new_config": {
+                    "min_storage_deposit": "1000000000000000000000000",
+                    "max_storage_balance": "3000000000000000000000000",
+                    "allow_deposit_for_others": false,
+                    "deposit_fee_bps": 100,
+                },
+            }
+        })]
+    );
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_update_config_not_owner() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.update_config(config());
+}
+
+#[test]
+#[should_panic = "Maximum storage balance is less than minimum storage deposit"]
+fn test_update_config_invalid_bounds() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.update_config(Config {
+        max_storage_balance: Some(U128(ONE_NEAR / 2)),
+        ..config()
+    });
+}
+
+#[test]
+#[should_panic = "Deposit fee exceeds maximum"]
+fn test_update_config_invalid_deposit_fee() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.update_config(Config {
+        deposit_fee_bps: MAX_DEPOSIT_FEE_BPS + 1,
+        ..config()
+    });
+}
+
+#[test]
+#[should_panic = "Not enough minimum deposit to register account"]
+fn test_storage_deposit_below_configured_minimum() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id, Some(ONE_NEAR / 2), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(config()));
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+}
+
+#[test]
+fn test_storage_deposit_registration_only_configured_minimum() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id, Some(2 * ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(config()));
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_deposit(None, Some(true));
+
+    assert_eq!(storage_balance.total.0, ONE_NEAR);
+}
+
+#[test]
+#[should_panic = "Deposit for other accounts is not allowed"]
+fn test_storage_deposit_for_others_not_allowed() {
+    let context = build_default_context(accounts(1), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(config()));
+
+    testing_env!(context.build());
+    contract.storage_deposit(Some(accounts(2)), None);
+}
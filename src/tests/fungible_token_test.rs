@@ -0,0 +1,288 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+};
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{accounts, get_created_receipts};
+use near_sdk::{testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+const TOTAL_SUPPLY: u128 = 1_000;
+
+/// Returns contract with registered accounts, the first one holds the whole token supply
+fn init_with_supply(account_ids: &[AccountId]) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    for account_id in account_ids {
+        contract.insert_account(account_id, Account::new(account_id, Some(ONE_NEAR)));
+    }
+
+    contract.ft_mint(account_ids[0].clone(), U128(TOTAL_SUPPLY), None);
+
+    contract
+}
+
+pub(super) fn ft_metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_owned(),
+        name: "Token".to_owned(),
+        symbol: "TKN".to_owned(),
+        icon: None,
+        reference: None,
+        reference_hash: None,
+        decimals: 24,
+    }
+}
+
+#[test]
+fn test_ft_mint() {
+    let contract = init_with_supply(&[accounts(1)]);
+
+    assert_eq!(contract.ft_total_supply(), U128(TOTAL_SUPPLY));
+    assert_eq!(contract.ft_balance_of(accounts(1)), U128(TOTAL_SUPPLY));
+    assert_eq!(contract.ft_balance_of(accounts(2)), U128(0));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_mint",
+            "data": [{
+                "owner_id": "bob",
+                "amount": "1000",
+            }]
+        })]
+    );
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_ft_mint_not_registered() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.ft_mint(accounts(1), U128(TOTAL_SUPPLY), None);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_ft_mint_not_owner() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_mint(accounts(1), U128(TOTAL_SUPPLY), None);
+}
+
+#[test]
+fn test_ft_metadata() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    contract.set_ft_metadata(ft_metadata());
+
+    assert_eq!(contract.ft_metadata().symbol, "TKN");
+}
+
+#[test]
+#[should_panic = "Token metadata is not set"]
+fn test_ft_metadata_not_set() {
+    let contract = init_with_supply(&[accounts(1)]);
+
+    contract.ft_metadata();
+}
+
+#[test]
+fn test_ft_transfer() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer(accounts(2), U128(100), Some("memo".to_owned()));
+
+    assert_eq!(
+        contract.ft_balance_of(accounts(1)),
+        U128(TOTAL_SUPPLY - 100)
+    );
+    assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+    assert_eq!(contract.ft_total_supply(), U128(TOTAL_SUPPLY));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_transfer",
+            "data": [{
+                "old_owner_id": "bob",
+                "new_owner_id": "charlie",
+                "amount": "100",
+                "memo": "memo",
+            }]
+        })]
+    );
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_ft_transfer_to_not_registered() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer(accounts(2), U128(100), None);
+}
+
+#[test]
+#[should_panic = "The account doesn't have enough balance"]
+fn test_ft_transfer_not_enough_balance() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer(accounts(2), U128(TOTAL_SUPPLY + 1), None);
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_ft_transfer_frozen_account() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    contract.freeze_account(accounts(1));
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer(accounts(2), U128(100), None);
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of exactly 1 yoctoNEAR"]
+fn test_ft_transfer_without_one_yocto() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), None, None);
+
+    testing_env!(context.build());
+    contract.ft_transfer(accounts(2), U128(100), None);
+}
+
+#[test]
+fn test_ft_transfer_call() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer_call(accounts(2), U128(100), None, "msg".to_owned());
+
+    assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+    assert_eq!(get_created_receipts()[0].receiver_id, accounts(2));
+}
+
+#[test]
+fn test_ft_resolve_transfer_refund() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer_call(accounts(2), U128(100), None, "msg".to_owned());
+
+    let context = build_default_context(accounts(0), None, None);
+
+    // receiver returns 40 of 100 tokens as unused
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"\"40\"".to_vec())]
+    );
+    let used_amount = contract.ft_resolve_transfer(accounts(1), accounts(2), U128(100));
+
+    assert_eq!(used_amount, U128(60));
+    assert_eq!(contract.ft_balance_of(accounts(1)), U128(TOTAL_SUPPLY - 60));
+    assert_eq!(contract.ft_balance_of(accounts(2)), U128(60));
+    assert_eq!(get_events()[0]["data"][0]["memo"], "refund");
+}
+
+#[test]
+fn test_ft_resolve_transfer_burns_refund_of_unregistered_sender() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.ft_transfer_call(accounts(2), U128(100), None, "msg".to_owned());
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    // sender is unregistered while the transfer is in flight
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let used_amount = contract.ft_resolve_transfer(accounts(1), accounts(2), U128(100));
+
+    assert_eq!(used_amount, U128(100));
+    assert_eq!(contract.ft_balance_of(accounts(2)), U128(0));
+    assert_eq!(contract.ft_total_supply(), U128(0));
+    assert_eq!(get_events()[0]["event"], "ft_burn");
+}
+
+#[test]
+#[should_panic = "Unable to unregister an account with positive token balance without `force` set to `true`"]
+fn test_storage_unregister_with_token_balance() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_unregister(None);
+}
+
+#[test]
+fn test_storage_force_unregister_burns_token_balance() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(Some(true)));
+
+    assert_eq!(contract.ft_total_supply(), U128(0));
+    assert_eq!(
+        get_events()[0],
+        json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_burn",
+            "data": [{
+                "owner_id": "bob",
+                "amount": "1000",
+                "memo": "unregister",
+            }]
+        })
+    );
+}
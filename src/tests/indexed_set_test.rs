@@ -0,0 +1,70 @@
+use crate::indexed_set::IndexedSet;
+use crate::storage::StorageKey;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::test_utils::accounts;
+use near_sdk::{env, testing_env, AccountId};
+
+use super::common::*;
+
+fn elements(set: &IndexedSet<AccountId>, from_index: u32) -> Vec<AccountId> {
+    set.iter_from(from_index).cloned().collect()
+}
+
+#[test]
+fn test_insert_and_remove() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+
+    let mut set = IndexedSet::new(StorageKey::AllowList);
+
+    assert!(set.insert(accounts(1)));
+    assert!(set.insert(accounts(2)));
+    assert!(set.insert(accounts(3)));
+    assert!(!set.insert(accounts(2)));
+    assert_eq!(set.len(), 3);
+
+    // the last element takes index of the removed one
+    assert!(set.remove(&accounts(1)));
+    assert!(!set.remove(&accounts(1)));
+    assert!(!set.contains(&accounts(1)));
+    assert_eq!(elements(&set, 0), vec![accounts(3), accounts(2)]);
+
+    assert!(set.remove(&accounts(2)));
+    assert!(set.insert(accounts(4)));
+    assert_eq!(elements(&set, 0), vec![accounts(3), accounts(4)]);
+    assert!(set.contains(&accounts(4)));
+}
+
+#[test]
+fn test_iter_from_reads_only_requested_elements() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+
+    let account_ids: Vec<AccountId> = (1..=4)
+        .map(|i| format!("user{}.near", i).parse().unwrap())
+        .collect();
+
+    let mut set = IndexedSet::new(StorageKey::AllowList);
+    for account_id in &account_ids {
+        set.insert(account_id.clone());
+    }
+    set.flush();
+
+    let state = set.try_to_vec().unwrap();
+
+    // reading a page deep in the set costs the same as reading it from the start
+    let page_gas = |from_index| {
+        let set = IndexedSet::<AccountId>::try_from_slice(&state).unwrap();
+        let used_gas = env::used_gas();
+        assert_eq!(
+            set.iter_from(from_index).take(2).last(),
+            Some(&account_ids[from_index as usize + 1])
+        );
+        env::used_gas() - used_gas
+    };
+
+    assert_eq!(page_gas(2), page_gas(0));
+    assert_eq!(elements(&set, 2), account_ids[2..]);
+}
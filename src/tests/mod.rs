@@ -1,3 +1,26 @@
+mod account_test;
 pub(crate) mod automation;
 mod common;
+mod config_test;
+#[cfg(feature = "ft")]
+mod fungible_token_test;
+mod indexed_set_test;
+mod moderation_test;
+#[cfg(feature = "mt")]
+mod multi_token_test;
+#[cfg(feature = "nft")]
+mod non_fungible_token_test;
+mod owner_test;
+mod refunds_test;
+mod registration_policy_test;
+mod roles_test;
+mod source_metadata_test;
+#[allow(clippy::bool_assert_comparison)]
 mod storage_management_test;
+mod storage_test;
+mod storage_token_test;
+mod storage_tracked_test;
+mod storage_tracker_test;
+mod treasury_test;
+mod upgrade_test;
+mod views_test;
@@ -0,0 +1,177 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use near_contract_standards::storage_management::*;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::accounts;
+use near_sdk::{testing_env, AccountId, ONE_NEAR};
+
+fn init_with_account(account_id: &AccountId) -> Contract {
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.insert_account(account_id, Account::new(account_id, Some(ONE_NEAR)));
+
+    contract
+}
+
+fn freeze(contract: &mut Contract, account_id: &AccountId) {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.freeze_account(account_id.clone());
+}
+
+#[test]
+fn test_freeze_and_unfreeze_account() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+
+    freeze(&mut contract, &account_id);
+    assert!(contract.is_frozen(account_id.clone()));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "account_frozen",
+            "data": {
+                "account_id": "bob",
+            }
+        })]
+    );
+
+    contract.unfreeze_account(account_id.clone());
+    assert!(!contract.is_frozen(account_id));
+    assert_eq!(get_events()[1]["event"], "account_unfrozen");
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_frozen_account_deposit() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+    freeze(&mut contract, &account_id);
+
+    let context = build_default_context(accounts(2), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(Some(account_id), None);
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_frozen_account_withdraw() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+    freeze(&mut contract, &account_id);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw(None);
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_frozen_account_unregister() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+    freeze(&mut contract, &account_id);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+}
+
+#[test]
+#[should_panic = "Account is already frozen"]
+fn test_freeze_frozen_account() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+    freeze(&mut contract, &account_id);
+
+    contract.freeze_account(account_id);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_freeze_account_not_owner() {
+    let account_id = accounts(1);
+
+    let mut contract = init_with_account(&account_id);
+
+    let context = build_default_context(accounts(2), Some(1), None);
+
+    testing_env!(context.build());
+    contract.freeze_account(account_id);
+}
+
+#[test]
+fn test_force_unregister_account() {
+    let account_id = accounts(1);
+    let beneficiary_id = accounts(3);
+
+    let mut contract = init_with_account(&account_id);
+    freeze(&mut contract, &account_id);
+
+    contract.force_unregister_account(account_id.clone(), beneficiary_id.clone());
+
+    assert!(contract.storage_balance_of(account_id).is_none());
+    assert_eq!(contract.accounts_count(), 0);
+    assert_eq!(get_transfers(), vec![(beneficiary_id, ONE_NEAR)]);
+    assert_eq!(
+        get_events()[1],
+        json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "account_force_unregistered",
+            "data": {
+                "account_id": "bob",
+                "beneficiary_id": "danny",
+                "amount": "1000000000000000000000000",
+            }
+        })
+    );
+}
+
+#[test]
+fn test_force_unregister_sponsored_account() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+    let beneficiary_id = accounts(3);
+
+    let mut contract = init_with_account(&account_id);
+
+    let context = build_default_context(sponsor_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    // sponsored balance is returned to sponsor
+    testing_env!(context.build());
+    contract.force_unregister_account(account_id, beneficiary_id.clone());
+
+    assert_eq!(
+        get_transfers(),
+        vec![(sponsor_id, ONE_NEAR), (beneficiary_id, ONE_NEAR)]
+    );
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_force_unregister_not_registered_account() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.force_unregister_account(accounts(1), accounts(3));
+}
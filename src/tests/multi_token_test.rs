@@ -0,0 +1,487 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{accounts, get_created_receipts};
+use near_sdk::{testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+const SWORD: &str = "sword";
+const SHIELD: &str = "shield";
+const SUPPLY: u128 = 1_000;
+
+/// Returns contract with registered accounts, the first one holds the whole supply of both tokens
+fn init_with_supply(account_ids: &[AccountId]) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    for account_id in account_ids {
+        contract.insert_account(account_id, Account::new(account_id, Some(ONE_NEAR)));
+    }
+
+    contract.mt_mint(account_ids[0].clone(), SWORD.to_owned(), U128(SUPPLY), None);
+    contract.mt_mint(
+        account_ids[0].clone(),
+        SHIELD.to_owned(),
+        U128(SUPPLY),
+        None,
+    );
+
+    contract
+}
+
+fn token_ids(token_ids: &[&str]) -> Vec<String> {
+    token_ids
+        .iter()
+        .map(|token_id| token_id.to_string())
+        .collect()
+}
+
+fn amounts(amounts: &[u128]) -> Vec<U128> {
+    amounts.iter().copied().map(U128).collect()
+}
+
+/// Returns storage usage of `account_id` above the initial one
+fn balances_storage_usage(contract: &Contract, account_id: &AccountId) -> u64 {
+    contract.get_account(account_id).unwrap().storage_usage
+        - Account::initial_storage_usage(Some(account_id))
+}
+
+fn batch_transfer(contract: &mut Contract, token_ids: Vec<String>, amounts: Vec<U128>) {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_batch_transfer(accounts(2), token_ids, amounts, None, None);
+}
+
+#[test]
+fn test_mt_mint() {
+    let contract = init_with_supply(&[accounts(1)]);
+
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(1), token_ids(&[SWORD, SHIELD, "axe"])),
+        amounts(&[SUPPLY, SUPPLY, 0])
+    );
+    assert_eq!(
+        contract.mt_batch_supply(token_ids(&[SWORD, "axe"])),
+        vec![Some(U128(SUPPLY)), None]
+    );
+    assert!(balances_storage_usage(&contract, &accounts(1)) > 0);
+    assert_eq!(
+        get_events()[0],
+        json!({
+            "standard": "nep245",
+            "version": "1.0.0",
+            "event": "mt_mint",
+            "data": [{
+                "owner_id": "bob",
+                "token_ids": ["sword"],
+                "amounts": ["1000"],
+            }]
+        })
+    );
+}
+
+#[test]
+#[should_panic = "Not enough storage balance to cover storage usage"]
+fn test_mt_mint_not_enough_storage_balance() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    let required_deposit = Account::required_deposit(Some(&accounts(1)));
+    contract.insert_account(
+        &accounts(1),
+        Account::new(&accounts(1), Some(required_deposit.0)),
+    );
+
+    contract.mt_mint(accounts(1), SWORD.to_owned(), U128(SUPPLY), None);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_mt_mint_not_owner() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_mint(accounts(1), SWORD.to_owned(), U128(SUPPLY), None);
+}
+
+#[test]
+fn test_mt_transfer() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_transfer(
+        accounts(2),
+        SWORD.to_owned(),
+        U128(100),
+        None,
+        Some("memo".to_owned()),
+    );
+
+    assert_eq!(
+        contract.mt_balance_of(accounts(1), SWORD.to_owned()),
+        U128(SUPPLY - 100)
+    );
+    assert_eq!(
+        contract.mt_balance_of(accounts(2), SWORD.to_owned()),
+        U128(100)
+    );
+    assert!(balances_storage_usage(&contract, &accounts(2)) > 0);
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "nep245",
+            "version": "1.0.0",
+            "event": "mt_transfer",
+            "data": [{
+                "old_owner_id": "bob",
+                "new_owner_id": "charlie",
+                "token_ids": ["sword"],
+                "amounts": ["100"],
+                "memo": "memo",
+            }]
+        })]
+    );
+}
+
+#[test]
+fn test_mt_batch_transfer_whole_balance_releases_storage() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(
+        &mut contract,
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[SUPPLY, SUPPLY]),
+    );
+
+    assert_eq!(balances_storage_usage(&contract, &accounts(1)), 0);
+    assert!(balances_storage_usage(&contract, &accounts(2)) > 0);
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(2), token_ids(&[SWORD, SHIELD])),
+        amounts(&[SUPPLY, SUPPLY])
+    );
+}
+
+#[test]
+fn test_mt_batch_transfer_repeated_token_id() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(
+        &mut contract,
+        token_ids(&[SWORD, SHIELD, SWORD]),
+        amounts(&[100, 10, 200]),
+    );
+
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(2), token_ids(&[SWORD, SHIELD])),
+        amounts(&[300, 10])
+    );
+    assert_eq!(
+        get_events()[0]["data"][0]["amounts"],
+        json!(["100", "10", "200"])
+    );
+}
+
+#[test]
+#[should_panic = "The account doesn't have enough balance"]
+fn test_mt_batch_transfer_repeated_token_id_not_enough_balance() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(
+        &mut contract,
+        token_ids(&[SWORD, SWORD]),
+        amounts(&[SUPPLY, 1]),
+    );
+}
+
+#[test]
+#[should_panic = "The account doesn't have enough balance"]
+fn test_mt_batch_transfer_unknown_token_id() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(&mut contract, token_ids(&[SWORD, "axe"]), amounts(&[1, 1]));
+}
+
+#[test]
+#[should_panic = "Token ids should not be empty"]
+fn test_mt_batch_transfer_empty() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(&mut contract, vec![], vec![]);
+}
+
+#[test]
+#[should_panic = "Number of token ids and amounts should be equal"]
+fn test_mt_batch_transfer_length_mismatch() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(&mut contract, token_ids(&[SWORD, SHIELD]), amounts(&[1]));
+}
+
+#[test]
+#[should_panic = "The amount should be a positive number"]
+fn test_mt_batch_transfer_zero_amount() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(&mut contract, token_ids(&[SWORD, SHIELD]), amounts(&[1, 0]));
+}
+
+#[test]
+#[should_panic = "Approvals are not supported"]
+fn test_mt_batch_transfer_with_approval() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_batch_transfer(
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[1, 1]),
+        Some(vec![None, Some((accounts(3), 1))]),
+        None,
+    );
+}
+
+#[test]
+#[should_panic = "Sender and receiver should be different"]
+fn test_mt_transfer_to_self() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_transfer(accounts(1), SWORD.to_owned(), U128(1), None, None);
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_mt_transfer_to_not_registered() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_transfer(accounts(2), SWORD.to_owned(), U128(1), None, None);
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_mt_transfer_frozen_account() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    contract.freeze_account(accounts(1));
+
+    batch_transfer(&mut contract, token_ids(&[SWORD]), amounts(&[1]));
+}
+
+#[test]
+fn test_mt_resolve_transfer_refund() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_batch_transfer_call(
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[100, 100]),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    assert_eq!(get_created_receipts()[0].receiver_id, accounts(2));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    // receiver returns 40 swords and all shields as unused
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"[\"40\", \"100\"]".to_vec())]
+    );
+    let used_amounts = contract.mt_resolve_transfer(
+        vec![accounts(1), accounts(1)],
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[100, 100]),
+    );
+
+    assert_eq!(used_amounts, amounts(&[60, 0]));
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(1), token_ids(&[SWORD, SHIELD])),
+        amounts(&[SUPPLY - 60, SUPPLY])
+    );
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(2), token_ids(&[SWORD, SHIELD])),
+        amounts(&[60, 0])
+    );
+    assert_eq!(get_events()[0]["data"][0]["memo"], "refund");
+}
+
+#[test]
+fn test_mt_resolve_transfer_keeps_refund_of_sender_without_storage() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_batch_transfer_call(
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[SUPPLY, 100]),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    // sender withdraws storage released by the transfer while it is in flight
+    testing_env!(context.build());
+    contract.storage_withdraw(None);
+
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(storage_balance.available.0, 0);
+    let storage_usage = balances_storage_usage(&contract, &accounts(2));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let used_amounts = contract.mt_resolve_transfer(
+        vec![accounts(1), accounts(1)],
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[SUPPLY, 100]),
+    );
+
+    // emptied sword balance needs new storage, the existing shield balance doesn't
+    assert_eq!(used_amounts, amounts(&[SUPPLY, 0]));
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(1), token_ids(&[SWORD, SHIELD])),
+        amounts(&[0, SUPPLY])
+    );
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(2), token_ids(&[SWORD, SHIELD])),
+        amounts(&[SUPPLY, 0])
+    );
+    assert!(balances_storage_usage(&contract, &accounts(2)) < storage_usage);
+    assert_eq!(contract.mt_supply(SWORD.to_owned()), Some(U128(SUPPLY)));
+    assert_eq!(
+        contract.storage_balance_of(accounts(1)).unwrap().total,
+        storage_balance.total
+    );
+}
+
+#[test]
+fn test_mt_resolve_transfer_burns_refund_of_unregistered_sender() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.mt_batch_transfer_call(
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[SUPPLY, SUPPLY]),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    // sender is unregistered while the transfer is in flight
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let used_amounts = contract.mt_resolve_transfer(
+        vec![accounts(1), accounts(1)],
+        accounts(2),
+        token_ids(&[SWORD, SHIELD]),
+        amounts(&[SUPPLY, SUPPLY]),
+    );
+
+    assert_eq!(used_amounts, amounts(&[SUPPLY, SUPPLY]));
+    assert_eq!(
+        contract.mt_batch_supply(token_ids(&[SWORD, SHIELD])),
+        vec![Some(U128(0)), Some(U128(0))]
+    );
+    assert_eq!(balances_storage_usage(&contract, &accounts(2)), 0);
+    assert_eq!(get_events()[0]["event"], "mt_burn");
+}
+
+#[test]
+#[should_panic = "Unable to unregister an account owning multi tokens without `force` set to `true`"]
+fn test_storage_unregister_with_multi_tokens() {
+    let mut contract = init_with_supply(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_unregister(None);
+}
+
+#[test]
+fn test_storage_force_unregister_burns_multi_tokens() {
+    let mut contract = init_with_supply(&[accounts(1), accounts(2)]);
+
+    batch_transfer(&mut contract, token_ids(&[SWORD]), amounts(&[400]));
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(Some(true)));
+
+    assert_eq!(
+        contract.mt_batch_balance_of(accounts(1), token_ids(&[SWORD, SHIELD])),
+        amounts(&[0, 0])
+    );
+    assert_eq!(
+        contract.mt_batch_supply(token_ids(&[SWORD, SHIELD])),
+        vec![Some(U128(400)), Some(U128(0))]
+    );
+    assert_eq!(get_transfers(), vec![(accounts(1), ONE_NEAR)]);
+    assert_eq!(
+        get_events()[0],
+        json!({
+            "standard": "nep245",
+            "version": "1.0.0",
+            "event": "mt_burn",
+            "data": [{
+                "owner_id": "bob",
+                "token_ids": ["sword", "shield"],
+                "amounts": ["600", "1000"],
+                "memo": "unregister",
+            }]
+        })
+    );
+}
@@ -0,0 +1,447 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
+};
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{accounts, get_created_receipts};
+use near_sdk::{testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+const TOKEN_ID: &str = "token";
+
+/// Returns contract with registered accounts, the first one owns `TOKEN_ID`
+fn init_with_token(account_ids: &[AccountId]) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    for account_id in account_ids {
+        contract.insert_account(account_id, Account::new(account_id, Some(ONE_NEAR)));
+    }
+
+    contract.nft_mint(
+        TOKEN_ID.to_owned(),
+        account_ids[0].clone(),
+        token_metadata(),
+    );
+
+    contract
+}
+
+pub(super) fn token_metadata() -> TokenMetadata {
+    TokenMetadata {
+        title: Some("Token".to_owned()),
+        description: None,
+        media: None,
+        media_hash: None,
+        copies: None,
+        issued_at: None,
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: None,
+        reference: None,
+        reference_hash: None,
+    }
+}
+
+pub(super) fn nft_metadata() -> NFTContractMetadata {
+    NFTContractMetadata {
+        spec: NFT_METADATA_SPEC.to_owned(),
+        name: "Tokens".to_owned(),
+        symbol: "NFT".to_owned(),
+        icon: None,
+        base_uri: None,
+        reference: None,
+        reference_hash: None,
+    }
+}
+
+/// Returns storage usage of `account_id` above the initial one
+fn tokens_storage_usage(contract: &Contract, account_id: &AccountId) -> u64 {
+    contract.get_account(account_id).unwrap().storage_usage
+        - Account::initial_storage_usage(Some(account_id))
+}
+
+#[test]
+fn test_nft_mint() {
+    let contract = init_with_token(&[accounts(1)]);
+
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(1)
+    );
+    assert_eq!(contract.nft_total_supply(), U128(1));
+    assert_eq!(contract.nft_supply_for_owner(accounts(1)), U128(1));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "nep171",
+            "version": "1.0.0",
+            "event": "nft_mint",
+            "data": [{
+                "owner_id": "bob",
+                "token_ids": ["token"],
+            }]
+        })]
+    );
+}
+
+#[test]
+fn test_nft_mint_charges_token_owner() {
+    let contract = init_with_token(&[accounts(1)]);
+
+    let storage_usage = tokens_storage_usage(&contract, &accounts(1));
+    assert!(storage_usage > 0);
+
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(
+        storage_balance.available.0,
+        ONE_NEAR - contract.get_account(&accounts(1)).unwrap().locked_balance()
+    );
+}
+
+#[test]
+#[should_panic = "Not enough storage balance to cover storage usage"]
+fn test_nft_mint_not_enough_storage_balance() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    let required_deposit = Account::required_deposit(Some(&accounts(1)));
+    contract.insert_account(
+        &accounts(1),
+        Account::new(&accounts(1), Some(required_deposit.0)),
+    );
+
+    contract.nft_mint(TOKEN_ID.to_owned(), accounts(1), token_metadata());
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_nft_mint_not_registered() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.nft_mint(TOKEN_ID.to_owned(), accounts(1), token_metadata());
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_nft_mint_not_owner() {
+    let mut contract = init_with_token(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_mint("other".to_owned(), accounts(1), token_metadata());
+}
+
+#[test]
+fn test_nft_metadata() {
+    let mut contract = init_with_token(&[accounts(1)]);
+
+    contract.set_nft_metadata(nft_metadata());
+
+    assert_eq!(contract.nft_metadata().symbol, "NFT");
+}
+
+#[test]
+#[should_panic = "Contract metadata is not set"]
+fn test_nft_metadata_not_set() {
+    let contract = init_with_token(&[accounts(1)]);
+
+    contract.nft_metadata();
+}
+
+#[test]
+fn test_nft_transfer_moves_storage() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer(accounts(2), TOKEN_ID.to_owned(), None, None);
+
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(2)
+    );
+    assert_eq!(tokens_storage_usage(&contract, &accounts(1)), 0);
+    assert!(tokens_storage_usage(&contract, &accounts(2)) > 0);
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "nep171",
+            "version": "1.0.0",
+            "event": "nft_transfer",
+            "data": [{
+                "old_owner_id": "bob",
+                "new_owner_id": "charlie",
+                "token_ids": ["token"],
+            }]
+        })]
+    );
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_nft_transfer_to_not_registered() {
+    let mut contract = init_with_token(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer(accounts(2), TOKEN_ID.to_owned(), None, None);
+}
+
+#[test]
+#[should_panic = "Sender not approved"]
+fn test_nft_transfer_not_approved() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(2), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer(accounts(2), TOKEN_ID.to_owned(), None, None);
+}
+
+#[test]
+#[should_panic = "Account is frozen"]
+fn test_nft_transfer_frozen_account() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    contract.freeze_account(accounts(1));
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer(accounts(2), TOKEN_ID.to_owned(), None, None);
+}
+
+#[test]
+fn test_nft_approve_and_revoke() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let storage_usage = tokens_storage_usage(&contract, &accounts(1));
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_approve(TOKEN_ID.to_owned(), accounts(3), None);
+
+    assert!(contract.nft_is_approved(TOKEN_ID.to_owned(), accounts(3), Some(1)));
+    let approved_storage_usage = tokens_storage_usage(&contract, &accounts(1));
+    assert!(approved_storage_usage > storage_usage);
+
+    contract.nft_revoke(TOKEN_ID.to_owned(), accounts(3));
+
+    assert!(!contract.nft_is_approved(TOKEN_ID.to_owned(), accounts(3), None));
+    // next approval id of the token is kept
+    assert!(tokens_storage_usage(&contract, &accounts(1)) < approved_storage_usage);
+}
+
+#[test]
+fn test_nft_transfer_by_approved_account() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_approve(TOKEN_ID.to_owned(), accounts(3), None);
+
+    let context = build_default_context(accounts(3), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer(accounts(2), TOKEN_ID.to_owned(), Some(1), None);
+
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(2)
+    );
+    // approvals are cleared upon transfer
+    assert!(!contract.nft_is_approved(TOKEN_ID.to_owned(), accounts(3), None));
+    assert_eq!(tokens_storage_usage(&contract, &accounts(1)), 0);
+    assert_eq!(get_events()[0]["data"][0]["authorized_id"], "danny");
+}
+
+#[test]
+fn test_nft_resolve_transfer_revert() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let storage_usage = tokens_storage_usage(&contract, &accounts(1));
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer_call(
+        accounts(2),
+        TOKEN_ID.to_owned(),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    assert_eq!(get_created_receipts()[0].receiver_id, accounts(2));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    // receiver asks to return the token
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"true".to_vec())]
+    );
+    let kept = contract.nft_resolve_transfer(accounts(1), accounts(2), TOKEN_ID.to_owned(), None);
+
+    assert!(!kept);
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(1)
+    );
+    assert_eq!(tokens_storage_usage(&contract, &accounts(1)), storage_usage);
+    assert_eq!(tokens_storage_usage(&contract, &accounts(2)), 0);
+}
+
+#[test]
+fn test_nft_resolve_transfer_keeps_token_of_unregistered_owner() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer_call(
+        accounts(2),
+        TOKEN_ID.to_owned(),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    // previous owner is unregistered while the transfer is in flight
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let kept = contract.nft_resolve_transfer(accounts(1), accounts(2), TOKEN_ID.to_owned(), None);
+
+    assert!(kept);
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(2)
+    );
+}
+
+#[test]
+fn test_nft_resolve_transfer_keeps_token_of_owner_without_storage() {
+    let mut contract = init_with_token(&[accounts(1), accounts(2)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.nft_transfer_call(
+        accounts(2),
+        TOKEN_ID.to_owned(),
+        None,
+        None,
+        "msg".to_owned(),
+    );
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    // previous owner withdraws storage released by the transfer while it is in flight
+    testing_env!(context.build());
+    contract.storage_withdraw(None);
+
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(storage_balance.available.0, 0);
+    let storage_usage = tokens_storage_usage(&contract, &accounts(2));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let kept = contract.nft_resolve_transfer(accounts(1), accounts(2), TOKEN_ID.to_owned(), None);
+
+    assert!(kept);
+    assert_eq!(
+        contract.nft_token(TOKEN_ID.to_owned()).unwrap().owner_id,
+        accounts(2)
+    );
+    assert_eq!(tokens_storage_usage(&contract, &accounts(1)), 0);
+    assert_eq!(tokens_storage_usage(&contract, &accounts(2)), storage_usage);
+    assert_eq!(
+        contract.storage_balance_of(accounts(1)).unwrap().total,
+        storage_balance.total
+    );
+    assert!(get_events().is_empty());
+}
+
+#[test]
+#[should_panic = "Unable to unregister an account owning non-fungible tokens without `force` set to `true`"]
+fn test_storage_unregister_with_tokens() {
+    let mut contract = init_with_token(&[accounts(1)]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_unregister(None);
+}
+
+#[test]
+fn test_storage_force_unregister_burns_tokens() {
+    let mut contract = init_with_token(&[accounts(1)]);
+    contract.nft_mint("token2".to_owned(), accounts(1), token_metadata());
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(Some(true)));
+
+    assert!(contract.nft_token(TOKEN_ID.to_owned()).is_none());
+    assert_eq!(contract.nft_total_supply(), U128(0));
+    assert_eq!(contract.nft_supply_for_owner(accounts(1)), U128(0));
+    assert_eq!(get_transfers(), vec![(accounts(1), ONE_NEAR)]);
+    assert_eq!(
+        get_events()[0],
+        json!({
+            "standard": "nep171",
+            "version": "1.0.0",
+            "event": "nft_burn",
+            "data": [{
+                "owner_id": "bob",
+                "token_ids": ["token", "token2"],
+                "memo": "unregister",
+            }]
+        })
+    );
+}
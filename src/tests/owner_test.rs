@@ -0,0 +1,432 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use crate::misc::{MethodGroup, RunningState};
+use near_contract_standards::storage_management::*;
+use near_sdk::test_utils::{accounts, get_logs};
+use near_sdk::{testing_env, ONE_NEAR};
+
+#[test]
+fn test_pause_and_resume() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    assert_eq!(contract.get_running_state(), RunningState::Running);
+
+    contract.pause();
+    assert_eq!(contract.get_running_state(), RunningState::Paused);
+
+    contract.resume();
+    assert_eq!(contract.get_running_state(), RunningState::Running);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_pause_not_owner() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.pause();
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_resume_not_owner() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.running_state = RunningState::Paused;
+
+    testing_env!(context.build());
+    contract.resume();
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of exactly 1 yoctoNEAR"]
+fn test_pause_without_one_yocto() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), None, None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.pause();
+}
+
+#[test]
+#[should_panic = "Contract is already paused"]
+fn test_pause_twice() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.pause();
+    contract.pause();
+}
+
+#[test]
+#[should_panic = "Contract is not paused"]
+fn test_resume_not_paused() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.resume();
+}
+
+#[test]
+#[should_panic = "Contract paused"]
+fn test_storage_deposit_while_paused() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.running_state = RunningState::Paused;
+
+    testing_env!(context.build());
+    contract.storage_deposit(account_id.into(), None);
+}
+
+#[test]
+#[should_panic = "Contract paused"]
+fn test_storage_withdraw_while_paused() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+    contract.flush(); // flush content before use env::storage_usage()
+    contract.running_state = RunningState::Paused;
+
+    testing_env!(context.build());
+    contract.storage_withdraw(None);
+}
+
+#[test]
+#[should_panic = "Contract paused"]
+fn test_storage_unregister_while_paused() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+    contract.flush(); // flush content before use env::storage_usage()
+    contract.running_state = RunningState::Paused;
+
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+}
+
+#[test]
+fn test_pause_and_resume_methods() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    assert!(contract.get_paused_methods().is_empty());
+
+    contract.pause_methods(MethodGroup::Unregister);
+    contract.pause_methods(MethodGroup::Deposits);
+    assert_eq!(
+        contract.get_paused_methods(),
+        vec![MethodGroup::Deposits, MethodGroup::Unregister]
+    );
+    assert_eq!(contract.get_running_state(), RunningState::Running);
+
+    contract.resume_methods(MethodGroup::Deposits);
+    assert_eq!(contract.get_paused_methods(), vec![MethodGroup::Unregister]);
+}
+
+#[test]
+#[should_panic = "Methods paused"]
+fn test_storage_deposit_while_deposits_paused() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.pause_methods(MethodGroup::Deposits);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(account_id.into(), None);
+}
+
+#[test]
+fn test_storage_withdraw_while_deposits_paused() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+    contract.flush(); // flush content before use env::storage_usage()
+
+    testing_env!(context.build());
+    contract.pause_methods(MethodGroup::Deposits);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    // users are still able to exit while deposits are paused
+    testing_env!(context.build());
+    contract.storage_withdraw(None);
+    contract.storage_unregister(Some(true));
+
+    assert!(contract.storage_balance_of(account_id).is_none());
+}
+
+#[test]
+#[should_panic = "Methods paused"]
+fn test_storage_balance_of_while_views_paused() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.pause_methods(MethodGroup::Views);
+    contract.storage_balance_of(accounts(1));
+}
+
+#[test]
+#[should_panic = "Methods are already paused"]
+fn test_pause_methods_twice() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.pause_methods(MethodGroup::Withdrawals);
+    contract.pause_methods(MethodGroup::Withdrawals);
+}
+
+#[test]
+#[should_panic = "Methods are not paused"]
+fn test_resume_methods_not_paused() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.resume_methods(MethodGroup::Withdrawals);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_pause_methods_not_owner() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.pause_methods(MethodGroup::Deposits);
+}
+
+#[test]
+fn test_ownership_transfer() {
+    let owner_id = accounts(0);
+    let new_owner_id = accounts(1);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id.clone()), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(new_owner_id.clone());
+
+    // ownership isn't transferred until accepted
+    assert_eq!(contract.owner_id(), owner_id);
+    assert_eq!(contract.pending_owner_id(), Some(new_owner_id.clone()));
+
+    let context = build_default_context(new_owner_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+    contract.accept_ownership();
+
+    assert_eq!(contract.owner_id(), new_owner_id);
+    assert_eq!(contract.pending_owner_id(), None);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"template-contract","version":"1.0.0","event":"ownership_transferred","data":{"old_owner_id":"alice","new_owner_id":"bob"}}"#
+        ]
+    );
+}
+
+#[test]
+fn test_propose_owner_event() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(accounts(1));
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"template-contract","version":"1.0.0","event":"owner_proposed","data":{"owner_id":"alice","proposed_owner_id":"bob"}}"#
+        ]
+    );
+}
+
+#[test]
+fn test_propose_owner_overrides_previous_proposal() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(accounts(1));
+    contract.propose_owner(accounts(2));
+
+    assert_eq!(contract.pending_owner_id(), Some(accounts(2)));
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_propose_owner_not_owner() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(account_id);
+}
+
+#[test]
+#[should_panic = "Account is already an owner"]
+fn test_propose_current_owner() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id.clone()), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(owner_id);
+}
+
+#[test]
+#[should_panic = "Not a proposed owner"]
+fn test_accept_ownership_not_proposed_account() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(accounts(1));
+
+    let context = build_default_context(accounts(2), Some(1), None);
+
+    testing_env!(context.build());
+    contract.accept_ownership();
+}
+
+#[test]
+#[should_panic = "Not a proposed owner"]
+fn test_accept_ownership_without_proposal() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.accept_ownership();
+}
+
+#[test]
+fn test_cancel_owner_proposal() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id.clone()), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(accounts(1));
+    contract.cancel_owner_proposal();
+
+    assert_eq!(contract.owner_id(), owner_id);
+    assert_eq!(contract.pending_owner_id(), None);
+    assert_eq!(
+        get_logs()[1],
+        r#"EVENT_JSON:{"standard":"template-contract","version":"1.0.0","event":"owner_proposal_cancelled","data":{"owner_id":"alice","proposed_owner_id":"bob"}}"#
+    );
+}
+
+#[test]
+#[should_panic = "No pending owner proposal"]
+fn test_cancel_owner_proposal_without_proposal() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.cancel_owner_proposal();
+}
+
+#[test]
+#[should_panic = "Not a proposed owner"]
+fn test_accept_cancelled_ownership() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(accounts(1));
+    contract.cancel_owner_proposal();
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.accept_ownership();
+}
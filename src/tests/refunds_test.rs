@@ -0,0 +1,92 @@
+use super::super::Contract;
+use super::common::*;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::accounts;
+use near_sdk::{testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+/// Holds `amount` for `account_id` as a failed transfer to the unregistered account does
+fn init_with_refund(account_id: AccountId, amount: u128) -> Contract {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.on_storage_withdraw(account_id, U128(amount));
+
+    contract
+}
+
+#[test]
+fn test_claim_refund() {
+    let mut contract = init_with_refund(accounts(1), 42);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert_eq!(contract.claim_refund(), U128(42));
+
+    assert_eq!(contract.refund_of(accounts(1)), U128(0));
+    assert_eq!(get_transfers(), vec![(accounts(1), 42)]);
+    assert_eq!(get_events()[0]["event"], "refund_claimed");
+}
+
+#[test]
+fn test_refunds_accumulate() {
+    let mut contract = init_with_refund(accounts(1), 42);
+
+    contract.on_storage_withdraw(accounts(1), U128(8));
+
+    assert_eq!(contract.refund_of(accounts(1)), U128(50));
+    assert_eq!(contract.refund_of(accounts(2)), U128(0));
+}
+
+#[test]
+fn test_on_refund_claim_failed_transfer() {
+    let mut contract = init_with_refund(accounts(1), 42);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.claim_refund();
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_refund_claim(accounts(1), U128(42));
+
+    assert_eq!(contract.refund_of(accounts(1)), U128(42));
+    assert_eq!(get_events()[0]["event"], "refund_held");
+}
+
+#[test]
+#[should_panic = "No refund to claim"]
+fn test_claim_refund_of_another_account() {
+    let mut contract = init_with_refund(accounts(1), 42);
+
+    let context = build_default_context(accounts(2), Some(1), None);
+
+    testing_env!(context.build());
+    contract.claim_refund();
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of exactly 1 yoctoNEAR"]
+fn test_claim_refund_without_yocto() {
+    let mut contract = init_with_refund(accounts(1), 42);
+
+    let context = build_default_context(accounts(1), None, None);
+
+    testing_env!(context.build());
+    contract.claim_refund();
+}
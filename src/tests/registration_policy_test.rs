@@ -0,0 +1,282 @@
+use super::super::registration_policy::RegistrationPolicy;
+use super::super::Contract;
+use super::common::*;
+use near_contract_standards::storage_management::*;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::accounts;
+use near_sdk::{env, testing_env, AccountId, Balance, ONE_NEAR};
+
+fn init_with_policy(policy: RegistrationPolicy, account_ids: Vec<AccountId>) -> Contract {
+    let context = build_default_context(accounts(0), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    match policy {
+        RegistrationPolicy::AllowList => contract.add_to_allow_list(account_ids),
+        RegistrationPolicy::DenyList => contract.add_to_deny_list(account_ids),
+        RegistrationPolicy::Open => {}
+    }
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.set_registration_policy(policy);
+
+    contract
+}
+
+fn register(contract: &mut Contract, account_id: AccountId) {
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(Some(account_id), None);
+}
+
+#[test]
+fn test_set_registration_policy() {
+    let contract = init_with_policy(RegistrationPolicy::AllowList, vec![accounts(1)]);
+
+    assert_eq!(
+        contract.get_registration_policy(),
+        RegistrationPolicy::AllowList
+    );
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "registration_policy_updated",
+            "data": {
+                "old_policy": "open",
+                "new_policy": "allow_list",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_add_to_list_charges_storage() {
+    let context = build_default_context(accounts(0), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.flush();
+
+    let storage_usage = env::storage_usage();
+    contract.add_to_allow_list(vec![accounts(1), accounts(2), accounts(1)]);
+    let storage_cost =
+        Balance::from(env::storage_usage() - storage_usage) * env::storage_byte_cost();
+
+    assert!(storage_cost > 0);
+    // unused deposit is refunded
+    assert_eq!(
+        get_transfers(),
+        vec![(accounts(0), ONE_NEAR - storage_cost)]
+    );
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "allow_list_add",
+            "data": {
+                "account_ids": ["bob", "charlie"],
+            }
+        })]
+    );
+
+    // already listed accounts don't add storage
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.add_to_allow_list(vec![accounts(1)]);
+
+    assert_eq!(get_transfers(), vec![(accounts(0), 1)]);
+
+    // released storage is refunded on removal
+    testing_env!(context.build());
+    contract.remove_from_allow_list(vec![accounts(1), accounts(2)]);
+
+    assert_eq!(get_transfers(), vec![(accounts(0), storage_cost + 1)]);
+}
+
+#[test]
+#[should_panic = "Not enough deposit to cover storage of the list"]
+fn test_add_to_deny_list_not_enough_deposit() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.add_to_deny_list(vec![accounts(1)]);
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of at least 1 yoctoNEAR"]
+fn test_add_to_allow_list_without_deposit() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.add_to_allow_list(vec![]);
+}
+
+#[test]
+fn test_allow_list_registration() {
+    let mut contract = init_with_policy(RegistrationPolicy::AllowList, vec![accounts(1)]);
+
+    register(&mut contract, accounts(1));
+
+    assert!(contract.storage_balance_of(accounts(1)).is_some());
+}
+
+#[test]
+#[should_panic = "Account is not allowed to register"]
+fn test_allow_list_registration_not_allowed() {
+    let mut contract = init_with_policy(RegistrationPolicy::AllowList, vec![accounts(1)]);
+
+    register(&mut contract, accounts(2));
+}
+
+#[test]
+fn test_deny_list_registration() {
+    let mut contract = init_with_policy(RegistrationPolicy::DenyList, vec![accounts(1)]);
+
+    register(&mut contract, accounts(2));
+
+    assert!(contract.storage_balance_of(accounts(2)).is_some());
+}
+
+#[test]
+#[should_panic = "Account is not allowed to register"]
+fn test_deny_list_registration_not_allowed() {
+    let mut contract = init_with_policy(RegistrationPolicy::DenyList, vec![accounts(1)]);
+
+    register(&mut contract, accounts(1));
+}
+
+#[test]
+#[should_panic = "Account is not allowed to register"]
+fn test_deny_list_sponsor_not_allowed() {
+    let mut contract = init_with_policy(RegistrationPolicy::DenyList, vec![accounts(1)]);
+
+    let context = build_default_context(accounts(2), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(accounts(1));
+}
+
+#[test]
+fn test_deny_list_batch_skips_not_allowed() {
+    let mut contract = init_with_policy(RegistrationPolicy::DenyList, vec![accounts(1)]);
+
+    let context = build_default_context(accounts(2), Some(2 * ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(vec![accounts(2), accounts(1)], None);
+
+    // denied account doesn't fail the batch and gets no deposit
+    assert!(results[0].registered);
+    assert_eq!(
+        results[0].storage_balance.as_ref().unwrap().total.0,
+        2 * ONE_NEAR
+    );
+    assert_eq!(results[1].account_id, accounts(1));
+    assert!(!results[1].registered);
+    assert!(results[1].storage_balance.is_none());
+    assert!(contract.storage_balance_of(accounts(1)).is_none());
+}
+
+#[test]
+fn test_allow_list_batch_refunds_not_allowed() {
+    let mut contract = init_with_policy(RegistrationPolicy::AllowList, vec![]);
+
+    let context = build_default_context(accounts(2), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(vec![accounts(1), accounts(3)], None);
+
+    assert!(results
+        .iter()
+        .all(|result| !result.registered && result.storage_balance.is_none()));
+    assert_eq!(get_transfers(), vec![(accounts(2), ONE_NEAR)]);
+}
+
+#[test]
+fn test_registered_account_deposit_after_policy_change() {
+    let mut contract = init_with_policy(RegistrationPolicy::Open, vec![]);
+
+    register(&mut contract, accounts(1));
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.set_registration_policy(RegistrationPolicy::AllowList);
+
+    // already registered accounts aren't affected
+    register(&mut contract, accounts(1));
+
+    assert_eq!(
+        contract.storage_balance_of(accounts(1)).unwrap().total.0,
+        2 * ONE_NEAR
+    );
+}
+
+#[test]
+fn test_remove_from_lists() {
+    let mut contract = init_with_policy(
+        RegistrationPolicy::AllowList,
+        vec![accounts(1), accounts(2), accounts(3)],
+    );
+    let context = build_default_context(accounts(0), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.add_to_deny_list(vec![accounts(4)]);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.remove_from_allow_list(vec![accounts(2), accounts(5)]);
+    contract.remove_from_deny_list(vec![accounts(4)]);
+
+    assert_eq!(
+        contract.get_allow_list(None, None),
+        vec![accounts(1), accounts(3)]
+    );
+    assert_eq!(
+        contract.get_allow_list(Some(1.into()), Some(1)),
+        vec![accounts(3)]
+    );
+    assert!(contract.get_deny_list(None, None).is_empty());
+    // only actually removed accounts are logged
+    assert_eq!(
+        get_events()[0]["data"],
+        json!({
+            "account_ids": ["charlie"],
+        })
+    );
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_add_to_allow_list_not_owner() {
+    let mut contract = init_with_policy(RegistrationPolicy::Open, vec![]);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.add_to_allow_list(vec![accounts(1)]);
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of exactly 1 yoctoNEAR"]
+fn test_set_registration_policy_without_deposit() {
+    let mut contract = init_with_policy(RegistrationPolicy::Open, vec![]);
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+    contract.set_registration_policy(RegistrationPolicy::DenyList);
+}
@@ -0,0 +1,424 @@
+use super::super::Contract;
+use super::common::*;
+#[cfg(feature = "ft")]
+use super::fungible_token_test::ft_metadata;
+#[cfg(feature = "nft")]
+use super::non_fungible_token_test::{nft_metadata, token_metadata};
+use crate::config::Config;
+use crate::misc::{MethodGroup, RunningState};
+use crate::registration_policy::RegistrationPolicy;
+use crate::roles::Role;
+use crate::upgrade::{stage_code_from_input, MIN_UPGRADE_DELAY};
+use near_sdk::test_utils::{accounts, get_logs};
+use near_sdk::{env, testing_env, AccountId};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Privileged method call by its name
+type PrivilegedCall = (&'static str, fn(&mut Contract));
+
+/// Every privileged method, except `guardian_pause` and the raw `upgrade` entrypoint
+const PRIVILEGED_CALLS: &[PrivilegedCall] = &[
+    ("pause", |c| c.pause()),
+    ("resume", |c| c.resume()),
+    ("pause_methods", |c| c.pause_methods(MethodGroup::Deposits)),
+    ("resume_methods", |c| {
+        c.resume_methods(MethodGroup::Deposits)
+    }),
+    ("propose_owner", |c| c.propose_owner(accounts(2))),
+    ("cancel_owner_proposal", |c| c.cancel_owner_proposal()),
+    ("grant_role", |c| c.grant_role(Role::Guardian, accounts(2))),
+    ("revoke_role", |c| {
+        c.revoke_role(Role::Guardian, accounts(0))
+    }),
+    ("update_config", |c| c.update_config(Config::default())),
+    ("freeze_account", |c| c.freeze_account(accounts(2))),
+    ("unfreeze_account", |c| c.unfreeze_account(accounts(2))),
+    ("force_unregister_account", |c| {
+        c.force_unregister_account(accounts(2), accounts(1))
+    }),
+    ("set_registration_policy", |c| {
+        c.set_registration_policy(RegistrationPolicy::AllowList)
+    }),
+    ("add_to_allow_list", |c| {
+        c.add_to_allow_list(vec![accounts(2)])
+    }),
+    ("remove_from_allow_list", |c| {
+        c.remove_from_allow_list(vec![accounts(2)])
+    }),
+    ("add_to_deny_list", |c| {
+        c.add_to_deny_list(vec![accounts(2)])
+    }),
+    ("remove_from_deny_list", |c| {
+        c.remove_from_deny_list(vec![accounts(2)])
+    }),
+    ("update_contract_source_metadata", |c| {
+        c.update_contract_source_metadata(None)
+    }),
+    ("stage_code", |c| c.stage_code(vec![1])),
+    ("deploy_staged_code", |c| {
+        c.deploy_staged_code();
+    }),
+    ("cancel_staged_code", |c| c.cancel_staged_code()),
+    ("set_upgrade_delay", |c| {
+        c.set_upgrade_delay(MIN_UPGRADE_DELAY.into())
+    }),
+    ("set_storage_token", |c| {
+        c.set_storage_token(accounts(2), Some(1.into()))
+    }),
+    ("index_legacy_accounts", |c| {
+        c.index_legacy_accounts(vec![accounts(2)]);
+    }),
+    ("withdraw_treasury", |c| {
+        c.withdraw_treasury(1.into(), accounts(2))
+    }),
+    #[cfg(feature = "ft")]
+    ("set_ft_metadata", |c| c.set_ft_metadata(ft_metadata())),
+    #[cfg(feature = "ft")]
+    ("ft_mint", |c| c.ft_mint(accounts(2), 1.into(), None)),
+    #[cfg(feature = "nft")]
+    ("set_nft_metadata", |c| c.set_nft_metadata(nft_metadata())),
+    #[cfg(feature = "nft")]
+    ("nft_mint", |c| {
+        c.nft_mint("token".to_owned(), accounts(2), token_metadata());
+    }),
+    #[cfg(feature = "mt")]
+    ("mt_mint", |c| {
+        c.mt_mint(accounts(2), "token".to_owned(), 1.into(), None)
+    }),
+];
+
+/// Returns contract with `guardian_id` granted the guardian role
+fn init_with_guardian(guardian_id: &AccountId) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Guardian, guardian_id.clone());
+
+    contract
+}
+
+#[test]
+fn test_owner_has_all_roles() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), None, None);
+
+    let contract = Contract::init(Some(owner_id.clone()), None, None);
+
+    testing_env!(context.is_view(true).build());
+    assert!(contract.has_role(Role::Admin, owner_id.clone()));
+    assert!(contract.has_role(Role::Operator, owner_id.clone()));
+    assert!(contract.has_role(Role::Treasurer, owner_id.clone()));
+    assert!(contract.has_role(Role::Upgrader, owner_id.clone()));
+    assert!(contract.has_role(Role::Guardian, owner_id));
+    assert!(!contract.has_role(Role::Operator, accounts(1)));
+}
+
+#[test]
+fn test_grant_and_revoke_role() {
+    let owner_id = accounts(0);
+    let account_id = accounts(1);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, account_id.clone());
+
+    assert!(contract.has_role(Role::Operator, account_id.clone()));
+    assert!(!contract.has_role(Role::Treasurer, account_id.clone()));
+
+    contract.revoke_role(Role::Operator, account_id.clone());
+
+    assert!(!contract.has_role(Role::Operator, account_id.clone()));
+    assert!(contract.roles.get(&account_id).is_none());
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"template-contract","version":"1.0.0","event":"role_granted","data":{"role":"operator","account_id":"bob","sender_id":"alice"}}"#,
+            r#"EVENT_JSON:{"standard":"template-contract","version":"1.0.0","event":"role_revoked","data":{"role":"operator","account_id":"bob","sender_id":"alice"}}"#,
+        ]
+    );
+}
+
+#[test]
+fn test_admin_manages_operational_roles() {
+    let admin_id = accounts(1);
+    let account_id = accounts(2);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Admin, admin_id.clone());
+
+    let context = build_default_context(admin_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, account_id.clone());
+    contract.grant_role(Role::Treasurer, account_id.clone());
+    contract.grant_role(Role::Upgrader, account_id.clone());
+    contract.revoke_role(Role::Treasurer, account_id.clone());
+
+    assert!(contract.has_role(Role::Operator, account_id.clone()));
+    assert!(!contract.has_role(Role::Treasurer, account_id.clone()));
+    assert!(contract.has_role(Role::Upgrader, account_id));
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_admin_is_not_able_to_grant_admin_role() {
+    let admin_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Admin, admin_id.clone());
+
+    let context = build_default_context(admin_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Admin, accounts(2));
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_operator_is_not_able_to_grant_role() {
+    let operator_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, operator_id.clone());
+
+    let context = build_default_context(operator_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, accounts(2));
+}
+
+#[test]
+#[should_panic = "Account already has the role"]
+fn test_grant_role_twice() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, accounts(1));
+    contract.grant_role(Role::Operator, accounts(1));
+}
+
+#[test]
+#[should_panic = "Account doesn't have the role"]
+fn test_revoke_missing_role() {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, accounts(1));
+    contract.revoke_role(Role::Treasurer, accounts(1));
+}
+
+#[test]
+fn test_renounce_role() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, account_id.clone());
+    contract.grant_role(Role::Treasurer, account_id.clone());
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+    contract.renounce_role(Role::Operator);
+
+    assert!(!contract.has_role(Role::Operator, account_id.clone()));
+    assert!(contract.has_role(Role::Treasurer, account_id));
+}
+
+#[test]
+#[should_panic = "Account doesn't have the role"]
+fn test_renounce_missing_role() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.renounce_role(Role::Operator);
+}
+
+#[test]
+fn test_operator_pause_and_resume() {
+    let operator_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, operator_id.clone());
+
+    let context = build_default_context(operator_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.pause();
+    assert_eq!(contract.get_running_state(), RunningState::Paused);
+
+    contract.resume();
+    assert_eq!(contract.get_running_state(), RunningState::Running);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_treasurer_is_not_able_to_pause() {
+    let treasurer_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Treasurer, treasurer_id.clone());
+
+    let context = build_default_context(treasurer_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.pause();
+}
+
+#[test]
+fn test_new_owner_becomes_super_admin() {
+    let new_owner_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.propose_owner(new_owner_id.clone());
+
+    let context = build_default_context(new_owner_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+    contract.accept_ownership();
+
+    assert!(contract.has_role(Role::Admin, new_owner_id));
+    assert!(!contract.has_role(Role::Admin, accounts(0)));
+}
+
+#[test]
+fn test_guardian_pause() {
+    let guardian_id = accounts(1);
+
+    let mut contract = init_with_guardian(&guardian_id);
+
+    let context = build_default_context(guardian_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.guardian_pause();
+
+    assert_eq!(contract.get_running_state(), RunningState::Paused);
+}
+
+#[test]
+#[should_panic = "Contract is already paused"]
+fn test_guardian_pause_twice() {
+    let guardian_id = accounts(1);
+
+    let mut contract = init_with_guardian(&guardian_id);
+
+    let context = build_default_context(guardian_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.guardian_pause();
+    contract.guardian_pause();
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_operator_is_not_able_to_guardian_pause() {
+    let operator_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Operator, operator_id.clone());
+
+    let context = build_default_context(operator_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.guardian_pause();
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_admin_is_not_able_to_grant_guardian_role() {
+    let admin_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Admin, admin_id.clone());
+
+    let context = build_default_context(admin_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Guardian, accounts(2));
+}
+
+#[test]
+fn test_guardian_is_rejected_by_privileged_methods() {
+    let guardian_id = accounts(1);
+
+    let mut contract = init_with_guardian(&guardian_id);
+
+    let context = build_default_context(guardian_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.guardian_pause();
+
+    for (method, call) in PRIVILEGED_CALLS {
+        let err = catch_unwind(AssertUnwindSafe(|| call(&mut contract)))
+            .expect_err(&format!("Guardian is able to call `{}`", method));
+
+        assert_eq!(
+            err.downcast_ref::<String>().map(String::as_str),
+            Some("Not allowed"),
+            "Unexpected error of `{}`",
+            method
+        );
+    }
+
+    assert_eq!(contract.get_running_state(), RunningState::Paused);
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_guardian_is_rejected_by_upgrade_entrypoint() {
+    let guardian_id = accounts(1);
+
+    env::state_write(&init_with_guardian(&guardian_id));
+
+    let context = build_default_context(guardian_id, Some(1), None);
+
+    testing_env!(with_input(&context, b"\0asm code"));
+    stage_code_from_input();
+}
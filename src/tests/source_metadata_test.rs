@@ -0,0 +1,59 @@
+use super::super::Contract;
+use super::common::*;
+use crate::source_metadata::{ContractSourceMetadata, Standard};
+use near_sdk::test_utils::accounts;
+use near_sdk::testing_env;
+
+const LINK: &str = "https://github.com/example/template-contract";
+
+#[test]
+fn test_contract_source_metadata() {
+    let contract = Contract::init(Some(accounts(0)), Some(LINK.to_owned()), None);
+
+    let metadata = contract.contract_source_metadata();
+
+    assert_eq!(metadata.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(metadata.link.as_deref(), Some(LINK));
+    assert!(metadata.standards.contains(&Standard {
+        standard: "nep145".to_owned(),
+        version: "1.0.0".to_owned(),
+    }));
+    assert!(metadata.standards.contains(&Standard {
+        standard: "nep330".to_owned(),
+        version: "1.1.0".to_owned(),
+    }));
+}
+
+#[test]
+fn test_update_contract_source_metadata() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), Some(LINK.to_owned()), None);
+    contract.source_metadata.version = Some("0.0.1".to_owned());
+
+    testing_env!(context.build());
+
+    // version is refreshed, link is kept
+    contract.update_contract_source_metadata(None);
+    assert_eq!(
+        contract.contract_source_metadata(),
+        ContractSourceMetadata::new(Some(LINK.to_owned()))
+    );
+
+    let link = format!("{}/tree/v{}", LINK, env!("CARGO_PKG_VERSION"));
+    contract.update_contract_source_metadata(Some(link.clone()));
+    assert_eq!(contract.contract_source_metadata().link, Some(link));
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_update_contract_source_metadata_not_owner() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.update_contract_source_metadata(Some(LINK.to_owned()));
+}
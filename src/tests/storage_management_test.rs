@@ -1,10 +1,17 @@
 use super::super::account::Account;
 use super::super::Contract;
 use super::common::*;
+use crate::config::Config;
+use crate::registration_policy::RegistrationPolicy;
+use crate::storage_management::GAS_PER_BATCH_ACCOUNT;
 use crate::storage_tracker::StorageUsageTrackerData;
 use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
 use near_sdk::test_utils::accounts;
-use near_sdk::{env, testing_env, AccountId, ONE_NEAR};
+use near_sdk::{
+    env, testing_env, AccountId, Balance, Gas, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+};
 
 #[test]
 fn test_single_account_max_id_len_storage_deposit() {
@@ -15,13 +22,13 @@ fn test_single_account_max_id_len_storage_deposit() {
 
     let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     let storage_tracker = StorageUsageTrackerData::default().track();
 
-    testing_env!(context.build());
     contract.storage_deposit(account_id.clone().into(), Some(false));
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     let account_storage_used = storage_tracker.finish(0);
 
@@ -37,13 +44,13 @@ fn test_account_storage_deposit_registration_and_deposit() {
 
     let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     let storage_tracker = StorageUsageTrackerData::default().track();
 
-    testing_env!(context.build());
     contract.storage_deposit(account_id.clone().into(), Some(false));
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     let account_storage_used = storage_tracker.finish(0);
 
@@ -59,13 +66,13 @@ fn test_account_storage_deposit_registration_only() {
 
     let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     let storage_tracker = StorageUsageTrackerData::default().track();
 
-    testing_env!(context.build());
     contract.storage_deposit(account_id.clone().into(), Some(true));
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     let account_storage_used = storage_tracker.finish(0);
 
@@ -85,13 +92,13 @@ fn test_account_storage_deposit_registration_only_register_twice() {
 
     let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     let storage_tracker = StorageUsageTrackerData::default().track();
 
-    testing_env!(context.build());
     contract.storage_deposit(account_id.clone().into(), Some(true));
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     let account_storage_used = storage_tracker.finish(0);
 
@@ -112,14 +119,14 @@ fn test_account_storage_deposit_overflow() {
 
     let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     // register account with maximum allowed deposit
     contract.accounts.insert(
         account_id.clone(),
         Account::new(&account_id, Some(u128::MAX)).into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     testing_env!(context.build());
     contract.storage_deposit(account_id.clone().into(), Some(false));
@@ -132,7 +139,7 @@ fn test_storage_withdraw_not_enough_storage_panic() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     // register account with minimum required deposit
     contract.accounts.insert(
@@ -143,7 +150,7 @@ fn test_storage_withdraw_not_enough_storage_panic() {
         )
         .into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     // try to withdraw more than have
     testing_env!(context.build());
@@ -158,13 +165,13 @@ fn test_storage_withdraw() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     contract.accounts.insert(
         account_id.clone(),
         Account::new(&account_id, Some(ONE_NEAR)).into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     let minimum_account_deposit = Account::required_deposit(Some(&account_id)).0;
 
@@ -182,13 +189,13 @@ fn test_storage_unregister_without_force() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     contract.accounts.insert(
         account_id.clone(),
         Account::new(&account_id, Some(ONE_NEAR)).into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     testing_env!(context.build());
     let unregistered = contract.storage_unregister(Some(false));
@@ -202,12 +209,12 @@ fn test_storage_unregister_zero_balance() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     contract
         .accounts
         .insert(account_id.clone(), Account::new(&account_id, None).into());
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     testing_env!(context.build());
     let unregistered = contract.storage_unregister(Some(false));
@@ -221,13 +228,13 @@ fn test_storage_unregister_with_force() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     contract.accounts.insert(
         account_id.clone(),
         Account::new(&account_id, Some(ONE_NEAR)).into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     testing_env!(context.build());
     let unregistered = contract.storage_unregister(Some(true));
@@ -241,7 +248,7 @@ fn test_not_exist_account_storage_unregister() {
 
     let context = build_default_context(account_id.clone(), Some(1), None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     testing_env!(context.build());
     let unregistered = contract.storage_unregister(Some(false));
@@ -255,7 +262,7 @@ fn test_storage_balance_bounds() {
 
     let mut context = build_default_context(account_id.clone(), None, None);
 
-    let contract = Contract::init(Some(accounts(0)));
+    let contract = Contract::init(Some(accounts(0)), None, None);
 
     testing_env!(context.is_view(true).build());
     let balance_bounds = contract.storage_balance_bounds();
@@ -270,7 +277,7 @@ fn test_storage_balance_of_not_registered_user() {
 
     let mut context = build_default_context(account_id.clone(), None, None);
 
-    let contract = Contract::init(Some(accounts(0)));
+    let contract = Contract::init(Some(accounts(0)), None, None);
 
     testing_env!(context.is_view(true).build());
     assert!(contract.storage_balance_of(account_id).is_none());
@@ -282,13 +289,13 @@ fn test_storage_balance_of() {
 
     let mut context = build_default_context(account_id.clone(), None, None);
 
-    let mut contract = Contract::init(Some(accounts(0)));
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
 
     contract.accounts.insert(
         account_id.clone(),
         Account::new(&account_id, Some(ONE_NEAR)).into(),
     );
-    contract.accounts.flush(); // flush content before use env::storage_usage()
+    contract.flush(); // flush content before use env::storage_usage()
 
     testing_env!(context.is_view(true).build());
     let storage_balance = contract.storage_balance_of(account_id.clone()).unwrap();
@@ -299,3 +306,810 @@ fn test_storage_balance_of() {
         ONE_NEAR - Account::required_deposit(Some(&account_id)).0
     );
 }
+
+#[test]
+fn test_storage_register_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, Some(true));
+
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_register",
+            "data": {
+                "account_id": "bob",
+                "amount": Account::required_deposit(Some(&account_id)),
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_storage_deposit_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_deposit",
+            "data": {
+                "account_id": "bob",
+                "amount": "1000000000000000000000000",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_storage_deposit_registration_only_registered_account_no_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, Some(true));
+
+    assert!(get_events().is_empty());
+}
+
+#[test]
+fn test_storage_withdraw_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    contract.storage_withdraw(Some(U128(42)));
+
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_withdraw",
+            "data": {
+                "account_id": "bob",
+                "amount": "42",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_storage_unregister_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract
+        .accounts
+        .insert(account_id.clone(), Account::new(&account_id, None).into());
+
+    testing_env!(context.build());
+    contract.storage_unregister(None);
+
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_unregister",
+            "data": {
+                "account_id": "bob",
+                "amount": "0",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_storage_force_unregister_event() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    contract.storage_unregister(Some(true));
+
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_force_unregister",
+            "data": {
+                "account_id": "bob",
+                "amount": "1000000000000000000000000",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_on_storage_withdraw_failed_transfer() {
+    let account_id = accounts(1);
+
+    // callbacks are private, so predecessor is the contract itself
+    let context = build_default_context(accounts(0), None, None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_withdraw(account_id.clone(), U128(42));
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_balance,
+        ONE_NEAR + 42
+    );
+    assert_eq!(get_events()[0]["event"], "storage_withdraw_rollback");
+}
+
+#[test]
+fn test_on_storage_withdraw_failed_transfer_unregistered_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.storage_deposit(None, None);
+
+    // account is unregistered before the callback of its withdraw transfer
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw(Some(U128(42)));
+    assert!(contract.storage_unregister(Some(true)));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_withdraw(account_id.clone(), U128(42));
+
+    // account isn't registered again, amount is held for it instead of going to the treasury
+    assert!(contract.get_account(&account_id).is_err());
+    assert_eq!(contract.refund_of(account_id.clone()), U128(42));
+    assert_eq!(contract.treasury_balance(), U128(0));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "refund_held",
+            "data": {
+                "account_id": "bob",
+                "amount": "42",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_on_storage_withdraw_successful_transfer() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(accounts(0), None, None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(vec![])]
+    );
+    contract.on_storage_withdraw(account_id.clone(), U128(42));
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_balance,
+        ONE_NEAR
+    );
+    assert!(get_events().is_empty());
+}
+
+#[test]
+fn test_on_storage_unregister_failed_transfer() {
+    let account_id = accounts(1);
+
+    let mut context = build_default_context(accounts(0), None, None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(
+        context.block_timestamp(42).build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_unregister(
+        account_id.clone(),
+        U128(ONE_NEAR),
+        Account::initial_storage_usage(Some(&account_id)).into(),
+        7.into(),
+        true,
+    );
+
+    let account = contract.get_account(&account_id).unwrap();
+    assert_eq!(account.storage_balance, ONE_NEAR);
+    assert_eq!(account.registered_at, 7);
+    assert!(account.frozen);
+    assert_eq!(contract.accounts_count(), 1);
+    assert_eq!(get_events()[0]["event"], "storage_unregister_rollback");
+}
+
+fn max_storage_balance_config() -> Config {
+    Config {
+        max_storage_balance: Some(U128(3 * ONE_NEAR)),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn test_storage_deposit_registration_refunds_excess_over_maximum() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(4 * ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(max_storage_balance_config()));
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_deposit(None, None);
+
+    assert_eq!(storage_balance.total.0, 3 * ONE_NEAR);
+    assert_eq!(get_transfers(), vec![(account_id, ONE_NEAR)]);
+}
+
+#[test]
+fn test_storage_deposit_for_other_account_refunds_excess_to_payer() {
+    let account_id = accounts(1);
+    let payer_id = accounts(2);
+
+    let context = build_default_context(payer_id.clone(), Some(2 * ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(max_storage_balance_config()));
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(2 * ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_deposit(Some(account_id.clone()), None);
+
+    assert_eq!(storage_balance.total.0, 3 * ONE_NEAR);
+    assert_eq!(get_transfers(), vec![(payer_id, ONE_NEAR)]);
+    assert_eq!(
+        get_events()[0]["data"]["amount"],
+        "1000000000000000000000000"
+    );
+}
+
+#[test]
+fn test_storage_deposit_at_maximum_refunds_all() {
+    let account_id = accounts(1);
+    let payer_id = accounts(2);
+
+    let context = build_default_context(payer_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(max_storage_balance_config()));
+
+    contract.accounts.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(3 * ONE_NEAR)).into(),
+    );
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_deposit(Some(account_id.clone()), None);
+
+    assert_eq!(storage_balance.total.0, 3 * ONE_NEAR);
+    assert_eq!(get_transfers(), vec![(payer_id, ONE_NEAR)]);
+    assert!(get_events().is_empty());
+}
+
+#[test]
+fn test_storage_sponsor_registers_account() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(sponsor_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    let storage_tracker = StorageUsageTrackerData::default().track();
+
+    let storage_balance = contract.storage_sponsor(account_id.clone());
+    contract.flush(); // flush content before use env::storage_usage()
+
+    let account_storage_used = storage_tracker.finish(0);
+    let account = contract.get_account(&account_id).unwrap();
+
+    // sponsored balance isn't available to the account
+    assert_eq!(storage_balance.total.0, ONE_NEAR);
+    assert_eq!(storage_balance.available.0, 0);
+    assert_eq!(account.storage_usage, account_storage_used);
+    assert_eq!(contract.sponsor_of(account_id.clone()), Some(sponsor_id));
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_sponsor",
+            "data": {
+                "sponsor_id": "charlie",
+                "account_id": "bob",
+                "amount": "1000000000000000000000000",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_storage_withdraw_sponsored_account() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(sponsor_id, Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(account_id.clone(), Some(2 * ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    // only own deposit of the account is withdrawable
+    testing_env!(context.build());
+    let storage_balance = contract.storage_withdraw(None);
+
+    assert_eq!(storage_balance.total.0, ONE_NEAR);
+    assert_eq!(storage_balance.available.0, 0);
+}
+
+#[test]
+fn test_storage_unregister_sponsored_account() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(sponsor_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(account_id.clone(), Some(1), None);
+
+    // account without own balance could be unregistered without `force`
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(None));
+
+    assert_eq!(
+        get_transfers(),
+        vec![(sponsor_id.clone(), ONE_NEAR), (account_id.clone(), 0)]
+    );
+    assert_eq!(contract.sponsor_of(account_id), None);
+    assert_eq!(get_events()[0]["event"], "storage_sponsor_refund");
+}
+
+#[test]
+#[should_panic = "Unable to unregister a positive balance account without `force` set to `true`"]
+fn test_storage_unregister_sponsored_account_with_own_balance() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(sponsor_id, Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+
+    let context = build_default_context(account_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_unregister(None);
+}
+
+#[test]
+#[should_panic = "Account is sponsored by another account"]
+fn test_storage_sponsor_by_another_sponsor() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(accounts(2), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(accounts(3), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id);
+}
+
+#[test]
+#[should_panic = "Account can't sponsor itself"]
+fn test_storage_sponsor_itself() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id);
+}
+
+#[test]
+fn test_on_sponsor_refund_failed_transfer() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(accounts(0), None, None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_sponsor_refund(sponsor_id.clone(), account_id.clone(), U128(ONE_NEAR));
+
+    let account = contract.get_account(&account_id).unwrap();
+    assert_eq!(account.storage_balance, ONE_NEAR);
+    assert_eq!(account.sponsored_balance, ONE_NEAR);
+    assert_eq!(contract.sponsor_of(account_id), Some(sponsor_id));
+    assert_eq!(get_events()[0]["event"], "storage_sponsor_refund_rollback");
+}
+
+#[test]
+fn test_on_sponsor_refund_failed_transfer_registration_not_allowed() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.set_registration_policy(RegistrationPolicy::AllowList);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_sponsor_refund(sponsor_id.clone(), account_id.clone(), U128(ONE_NEAR));
+
+    // account isn't registered bypassing the policy, refund is held for the sponsor
+    assert!(contract.get_account(&account_id).is_err());
+    assert_eq!(contract.refund_of(sponsor_id), U128(ONE_NEAR));
+    assert_eq!(get_events()[0]["event"], "refund_held");
+}
+
+#[test]
+fn test_on_sponsor_refund_failed_transfer_below_min_deposit() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(accounts(0), None, None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_sponsor_refund(sponsor_id.clone(), account_id.clone(), U128(42));
+
+    assert!(contract.get_account(&account_id).is_err());
+    assert_eq!(contract.refund_of(sponsor_id), U128(42));
+}
+
+#[test]
+fn test_on_sponsor_refund_failed_transfer_account_sponsored_by_another() {
+    let account_id = accounts(1);
+    let sponsor_id = accounts(2);
+
+    let context = build_default_context(accounts(3), Some(ONE_NEAR), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_sponsor(account_id.clone());
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_sponsor_refund(sponsor_id.clone(), account_id.clone(), U128(42));
+
+    // refund isn't given to the account sponsored by another account
+    let account = contract.get_account(&account_id).unwrap();
+    assert_eq!(account.storage_balance, ONE_NEAR);
+    assert_eq!(account.sponsored_balance, ONE_NEAR);
+    assert_eq!(contract.refund_of(sponsor_id), U128(42));
+}
+
+#[test]
+fn test_storage_deposit_batch_registration_only() {
+    let payer_id = accounts(0);
+    let account_ids = vec![
+        accounts(1),
+        AccountId::new_unchecked("a-much-longer-account-id.near".to_owned()),
+    ];
+
+    let min_deposits: Vec<Balance> = account_ids
+        .iter()
+        .map(|account_id| Account::required_deposit(Some(account_id)).0)
+        .collect();
+    let deposit = min_deposits.iter().sum::<Balance>() + 42;
+
+    let context = build_default_context(payer_id.clone(), Some(deposit), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(account_ids.clone(), Some(true));
+
+    assert_eq!(results.len(), 2);
+    for ((result, account_id), min_deposit) in results.iter().zip(&account_ids).zip(&min_deposits) {
+        assert_eq!(&result.account_id, account_id);
+        assert!(result.registered);
+        assert_eq!(
+            result.storage_balance.as_ref().unwrap().total.0,
+            *min_deposit
+        );
+    }
+    assert_eq!(get_transfers(), vec![(payer_id, 42)]);
+    assert_eq!(contract.accounts_count(), 2);
+}
+
+#[test]
+fn test_storage_deposit_batch_splits_deposit() {
+    let payer_id = accounts(0);
+    let account_ids = vec![accounts(1), accounts(2), accounts(3)];
+
+    let min_deposits: Vec<Balance> = account_ids
+        .iter()
+        .map(|account_id| Account::required_deposit(Some(account_id)).0)
+        .collect();
+    let deposit = min_deposits.iter().sum::<Balance>() + 3 * ONE_NEAR + 2;
+
+    let context = build_default_context(payer_id.clone(), Some(deposit), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(account_ids, None);
+
+    // the rest of deposit is split equally, indivisible remainder is refunded
+    for (result, min_deposit) in results.iter().zip(&min_deposits) {
+        assert_eq!(
+            result.storage_balance.as_ref().unwrap().total.0,
+            min_deposit + ONE_NEAR
+        );
+        assert_eq!(
+            result.storage_balance.as_ref().unwrap().available.0,
+            ONE_NEAR
+        );
+    }
+    assert_eq!(get_transfers(), vec![(payer_id, 2)]);
+}
+
+#[test]
+fn test_storage_deposit_batch_skips_registered_and_duplicates() {
+    let payer_id = accounts(0);
+    let account_id = accounts(1);
+    let registered_id = accounts(2);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.accounts.insert(
+        registered_id.clone(),
+        Account::new(&registered_id, Some(ONE_NEAR)).into(),
+    );
+
+    let min_deposit = Account::required_deposit(Some(&account_id)).0;
+
+    let context = build_default_context(payer_id.clone(), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(
+        vec![account_id.clone(), registered_id, account_id],
+        Some(true),
+    );
+
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.registered)
+            .collect::<Vec<_>>(),
+        vec![true, false, false]
+    );
+    assert_eq!(
+        results[1].storage_balance.as_ref().unwrap().total.0,
+        ONE_NEAR
+    );
+    assert_eq!(get_transfers(), vec![(payer_id, ONE_NEAR - min_deposit)]);
+    assert_eq!(get_events().len(), 1);
+}
+
+#[test]
+fn test_storage_deposit_batch_respects_maximum_storage_balance() {
+    let payer_id = accounts(0);
+    let account_ids = vec![accounts(1), accounts(2)];
+
+    let mut contract = Contract::init(Some(accounts(0)), None, Some(max_storage_balance_config()));
+
+    let context = build_default_context(payer_id.clone(), Some(10 * ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(account_ids, None);
+
+    assert!(results
+        .iter()
+        .all(|result| result.storage_balance.as_ref().unwrap().total.0 == 3 * ONE_NEAR));
+    assert_eq!(get_transfers(), vec![(payer_id, 4 * ONE_NEAR)]);
+}
+
+#[test]
+fn test_storage_deposit_batch_host_gas_per_account() {
+    // host function gas burnt by a batch of `size` accounts with the longest ids under allow list policy
+    fn batch_gas(size: u64) -> u64 {
+        let account_ids: Vec<AccountId> = (0..size)
+            .map(|index| format!("{:0>59}.near", index).parse().unwrap())
+            .collect();
+
+        let context = build_default_context(accounts(0), Some(1), None);
+
+        testing_env!(context.build());
+        let mut contract = Contract::init(Some(accounts(0)), None, None);
+        contract.set_registration_policy(RegistrationPolicy::AllowList);
+
+        let context = build_default_context(accounts(0), Some(ONE_NEAR), None);
+
+        testing_env!(context.build());
+        contract.add_to_allow_list(account_ids.clone());
+        contract.flush();
+
+        let context = build_default_context(accounts(0), Some(100 * ONE_NEAR), None);
+
+        testing_env!(context.build());
+        let used_gas = env::used_gas();
+        contract.storage_deposit_batch(account_ids, None);
+        contract.flush();
+
+        (env::used_gas() - used_gas).0
+    }
+
+    let gas_per_account = (batch_gas(11) - batch_gas(1)) / 10;
+
+    assert!(
+        2 * gas_per_account <= GAS_PER_BATCH_ACCOUNT.0,
+        "Gas per account {} leaves no margin",
+        gas_per_account
+    );
+}
+
+#[test]
+#[should_panic = "Not enough minimum deposit to register accounts"]
+fn test_storage_deposit_batch_not_enough_deposit() {
+    let account_ids = vec![accounts(1), accounts(2)];
+
+    let deposit = Account::required_deposit(Some(&accounts(1))).0;
+
+    let context = build_default_context(accounts(0), Some(deposit), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_deposit_batch(account_ids, Some(true));
+}
+
+#[test]
+#[should_panic = "Batch size exceeds limit of attached gas"]
+fn test_storage_deposit_batch_exceeds_gas_limit() {
+    let account_ids = vec![accounts(1), accounts(2), accounts(3)];
+
+    // enough gas for two accounts only
+    let context = build_default_context(accounts(0), Some(ONE_NEAR), Some(Gas(14_000_000_000_000)));
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.storage_deposit_batch(account_ids, Some(true));
+}
@@ -0,0 +1,63 @@
+use crate::storage::StorageKey;
+use near_sdk::borsh::BorshSerialize;
+
+fn prefix(key: StorageKey) -> Vec<u8> {
+    key.try_to_vec().unwrap()
+}
+
+#[test]
+fn test_storage_key_prefixes() {
+    let keys = [
+        (StorageKey::Accounts, 0),
+        (StorageKey::Roles, 1),
+        (StorageKey::StagedCode, 2),
+        (StorageKey::AccountIds, 3),
+        (StorageKey::AllowList, 4),
+        (StorageKey::DenyList, 5),
+        (StorageKey::StorageTokens, 6),
+        (StorageKey::FtMetadata, 7),
+        (StorageKey::NftOwners, 8),
+        (StorageKey::NftTokenMetadata, 9),
+        (StorageKey::NftEnumeration, 10),
+        (
+            StorageKey::NftTokensPerOwner {
+                account_hash: vec![],
+            },
+            11,
+        ),
+        (StorageKey::NftApprovals, 12),
+        (StorageKey::NftMetadata, 13),
+        (StorageKey::MtBalances, 14),
+        (
+            StorageKey::MtAccountBalances {
+                account_hash: vec![],
+            },
+            15,
+        ),
+        (StorageKey::MtTotalSupply, 16),
+        (StorageKey::StateVersion, 17),
+        (StorageKey::Refunds, 18),
+        (StorageKey::TokenRefunds, 19),
+    ];
+
+    // prefixes don't depend on enabled features
+    for (key, expected) in keys {
+        assert_eq!(prefix(key)[0], expected);
+    }
+}
+
+#[test]
+fn test_nested_storage_key_prefixes() {
+    assert_eq!(
+        prefix(StorageKey::NftTokensPerOwner {
+            account_hash: vec![7; 2],
+        }),
+        vec![11, 2, 0, 0, 0, 7, 7]
+    );
+    assert_eq!(
+        prefix(StorageKey::MtAccountBalances {
+            account_hash: vec![7; 2],
+        }),
+        vec![15, 2, 0, 0, 0, 7, 7]
+    );
+}
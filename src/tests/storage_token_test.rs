@@ -0,0 +1,476 @@
+use super::super::Contract;
+use super::common::*;
+use crate::config::Config;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::mock::VmAction;
+use near_sdk::serde_json::{self, json, Value};
+use near_sdk::test_utils::{accounts, get_created_receipts};
+use near_sdk::{
+    testing_env, AccountId, Balance, PromiseOrValue, PromiseResult, RuntimeFeesConfig, VMConfig,
+    ONE_NEAR,
+};
+
+/// yoctoNEAR credited per smallest token unit
+const RATE: Balance = 1_000_000_000_000_000_000;
+
+/// Amount of tokens covering 0.01 $NEAR of storage balance
+const AMOUNT: Balance = 10_000;
+
+fn token_id() -> AccountId {
+    "usdc.near".parse().unwrap()
+}
+
+fn init_with_token(config: Option<Config>) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(accounts(0)), None, config);
+
+    contract.set_storage_token(token_id(), Some(U128(RATE)));
+
+    contract
+}
+
+/// Transfers `amount` of tokens from `sender_id` to the contract, returns unused amount
+fn deposit_tokens(
+    contract: &mut Contract,
+    token_id: AccountId,
+    sender_id: AccountId,
+    amount: Balance,
+    msg: &str,
+) -> Balance {
+    let context = build_default_context(token_id, None, None);
+
+    testing_env!(context.build());
+    match contract.ft_on_transfer(sender_id, U128(amount), msg.to_owned()) {
+        PromiseOrValue::Value(unused_amount) => unused_amount.0,
+        PromiseOrValue::Promise(_) => panic!("Unexpected promise"),
+    }
+}
+
+/// Returns `ft_transfer` calls of created receipts by token contract
+fn get_token_transfers() -> Vec<(AccountId, Value)> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| {
+            let receiver_id = receipt.receiver_id;
+            receipt
+                .actions
+                .into_iter()
+                .filter_map(move |action| match action {
+                    VmAction::FunctionCall {
+                        function_name,
+                        args,
+                        ..
+                    } if function_name == "ft_transfer" => {
+                        Some((receiver_id.clone(), serde_json::from_slice(&args).unwrap()))
+                    }
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+#[test]
+fn test_set_storage_token() {
+    let mut contract = init_with_token(None);
+
+    assert_eq!(
+        contract.get_storage_tokens(),
+        vec![(token_id(), U128(RATE))]
+    );
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_token_updated",
+            "data": {
+                "token_id": "usdc.near",
+                "rate": "1000000000000000000",
+            }
+        })]
+    );
+
+    contract.set_storage_token(token_id(), None);
+
+    assert!(contract.get_storage_tokens().is_empty());
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_set_storage_token_not_owner() {
+    let mut contract = init_with_token(None);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.set_storage_token(token_id(), Some(U128(RATE)));
+}
+
+#[test]
+fn test_token_deposit_registers_account() {
+    let mut contract = init_with_token(None);
+
+    let unused_amount = deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    assert_eq!(unused_amount, 0);
+
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(storage_balance.total.0, AMOUNT * RATE);
+    // credited balance isn't withdrawable in $NEAR
+    assert_eq!(storage_balance.available.0, 0);
+
+    let token_credit = contract
+        .get_account(&accounts(1))
+        .unwrap()
+        .token_credit
+        .unwrap();
+    assert_eq!(token_credit.token_id, token_id());
+    assert_eq!(token_credit.amount, AMOUNT);
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "storage_token_deposit",
+            "data": {
+                "account_id": "bob",
+                "token_id": "usdc.near",
+                "amount": "10000",
+                "storage_amount": "10000000000000000000000",
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_token_deposit_for_other_account() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "charlie");
+
+    assert!(contract.storage_balance_of(accounts(1)).is_none());
+    assert_eq!(
+        contract.storage_balance_of(accounts(2)).unwrap().total.0,
+        AMOUNT * RATE
+    );
+}
+
+#[test]
+fn test_token_deposit_returns_unused_amount() {
+    let max_storage_balance = AMOUNT * RATE + RATE / 2;
+    let mut contract = init_with_token(Some(Config {
+        max_storage_balance: Some(U128(max_storage_balance)),
+        ..Config::default()
+    }));
+
+    // only smallest token units fully fitting under the maximum storage balance are used
+    let unused_amount = deposit_tokens(&mut contract, token_id(), accounts(1), 2 * AMOUNT, "");
+
+    assert_eq!(unused_amount, AMOUNT);
+    assert_eq!(
+        contract.storage_balance_of(accounts(1)).unwrap().total.0,
+        AMOUNT * RATE
+    );
+
+    let unused_amount = deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    assert_eq!(unused_amount, AMOUNT);
+}
+
+#[test]
+#[should_panic = "Token is not accepted for storage deposits"]
+fn test_token_deposit_not_accepted_token() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, accounts(5), accounts(1), AMOUNT, "");
+}
+
+#[test]
+#[should_panic = "Not enough minimum deposit to register account"]
+fn test_token_deposit_not_enough_to_register() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), 1, "");
+}
+
+#[test]
+#[should_panic = "Account storage is credited in another token"]
+fn test_token_deposit_in_another_token() {
+    let mut contract = init_with_token(None);
+    contract.set_storage_token(accounts(5), Some(U128(RATE)));
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+    deposit_tokens(&mut contract, accounts(5), accounts(1), AMOUNT, "");
+}
+
+#[test]
+fn test_storage_withdraw_token() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_withdraw_token(Some(U128(1_000)));
+
+    assert_eq!(storage_balance.total.0, (AMOUNT - 1_000) * RATE);
+    assert_eq!(
+        get_token_transfers(),
+        vec![(
+            token_id(),
+            json!({
+                "receiver_id": "bob",
+                "amount": "1000",
+                "memo": null,
+            })
+        )]
+    );
+}
+
+#[test]
+fn test_storage_withdraw_token_keeps_locked_balance() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw_token(None);
+
+    // the rest of credit covers used storage
+    let account = contract.get_account(&accounts(1)).unwrap();
+    let token_credit = account.token_credit.clone().unwrap();
+    assert!(token_credit.amount * RATE >= account.locked_balance());
+    assert!((token_credit.amount - 1) * RATE < account.locked_balance());
+}
+
+#[test]
+#[should_panic = "Not enough available tokens to withdraw"]
+fn test_storage_withdraw_token_locked_balance() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw_token(Some(U128(AMOUNT)));
+}
+
+#[test]
+#[should_panic = "Account has no token credit"]
+fn test_storage_withdraw_token_without_credit() {
+    let mut contract = init_with_token(None);
+
+    let context = build_default_context(accounts(1), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw_token(None);
+}
+
+#[test]
+fn test_storage_withdraw_near_excludes_credit() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, None);
+
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(storage_balance.total.0, ONE_NEAR + AMOUNT * RATE);
+    assert_eq!(storage_balance.available.0, ONE_NEAR);
+}
+
+#[test]
+fn test_on_storage_token_withdraw_rollback() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.storage_withdraw_token(Some(U128(1_000)));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_token_withdraw(accounts(1), token_id(), U128(1_000), U128(1_000 * RATE));
+
+    let account = contract.get_account(&accounts(1)).unwrap();
+    assert_eq!(account.token_credit.unwrap().amount, AMOUNT);
+    assert_eq!(account.storage_balance, AMOUNT * RATE);
+    assert_eq!(get_events()[0]["event"], "storage_token_withdraw_rollback");
+}
+
+/// Unregisters `accounts(1)` credited with `AMOUNT` of tokens and fails the transfer of returned tokens
+fn fail_unregistered_token_return(contract: &mut Contract) {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(None));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_token_withdraw(accounts(1), token_id(), U128(AMOUNT), U128(AMOUNT * RATE));
+}
+
+#[test]
+fn test_on_storage_token_withdraw_rollback_unregistered_account() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+    fail_unregistered_token_return(&mut contract);
+
+    // account isn't registered again bypassing registration checks, tokens are held for it
+    assert!(contract.storage_balance_of(accounts(1)).is_none());
+    assert_eq!(
+        contract.token_refund_of(accounts(1), token_id()),
+        U128(AMOUNT)
+    );
+    assert_eq!(get_events()[0]["event"], "token_refund_held");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert_eq!(contract.claim_token_refund(token_id()), U128(AMOUNT));
+
+    assert_eq!(contract.token_refund_of(accounts(1), token_id()), U128(0));
+    assert_eq!(
+        get_token_transfers(),
+        vec![(
+            token_id(),
+            json!({ "receiver_id": accounts(1), "amount": AMOUNT.to_string(), "memo": null })
+        )]
+    );
+    assert_eq!(get_events()[0]["event"], "token_refund_claimed");
+}
+
+#[test]
+fn test_on_storage_token_withdraw_rollback_credited_in_another_token() {
+    let other_token_id: AccountId = "usdt.near".parse().unwrap();
+
+    let mut contract = init_with_token(None);
+    contract.set_storage_token(other_token_id.clone(), Some(U128(RATE)));
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(None));
+
+    deposit_tokens(
+        &mut contract,
+        other_token_id.clone(),
+        accounts(1),
+        AMOUNT,
+        "",
+    );
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_storage_token_withdraw(accounts(1), token_id(), U128(AMOUNT), U128(AMOUNT * RATE));
+
+    let account = contract.get_account(&accounts(1)).unwrap();
+    assert_eq!(account.token_credit.unwrap().token_id, other_token_id);
+    assert_eq!(
+        contract.token_refund_of(accounts(1), token_id()),
+        U128(AMOUNT)
+    );
+}
+
+#[test]
+fn test_on_token_refund_claim_failed_transfer() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+    fail_unregistered_token_return(&mut contract);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.claim_token_refund(token_id());
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_token_refund_claim(accounts(1), token_id(), U128(AMOUNT));
+
+    assert_eq!(
+        contract.token_refund_of(accounts(1), token_id()),
+        U128(AMOUNT)
+    );
+}
+
+#[test]
+#[should_panic = "No token refund to claim"]
+fn test_claim_token_refund_of_another_token() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+    fail_unregistered_token_return(&mut contract);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.claim_token_refund("usdt.near".parse().unwrap());
+}
+
+#[test]
+fn test_storage_unregister_returns_tokens() {
+    let mut contract = init_with_token(None);
+
+    deposit_tokens(&mut contract, token_id(), accounts(1), AMOUNT, "");
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    // credited balance doesn't require `force`
+    assert!(contract.storage_unregister(None));
+
+    assert!(contract.storage_balance_of(accounts(1)).is_none());
+    assert_eq!(get_token_transfers()[0].1["amount"], "10000");
+    // no $NEAR is transferred for the credited balance
+    assert_eq!(get_transfers(), vec![(accounts(1), 0)]);
+}
@@ -0,0 +1,177 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use crate::ContractExt;
+use near_sdk::test_utils::accounts;
+use near_sdk::{env, near_bindgen, testing_env, AccountId, ONE_NEAR};
+use template_contract_macros::storage_tracked;
+
+/// Test methods writing raw entries, wrapped by the macro and by hand
+#[near_bindgen]
+impl Contract {
+    #[storage_tracked(account_id)]
+    pub fn test_write_tracked(&mut self, account_id: AccountId, key: String, value: String) {
+        env::storage_write(key.as_bytes(), value.as_bytes());
+    }
+
+    pub fn test_write_hand_tracked(&mut self, account_id: AccountId, key: String, value: String) {
+        self.track_storage_usage(&account_id, |_| {
+            env::storage_write(key.as_bytes(), value.as_bytes());
+        });
+    }
+
+    #[storage_tracked(account_id)]
+    pub fn test_write_optional_account(
+        &mut self,
+        account_id: Option<AccountId>,
+        key: String,
+        value: String,
+    ) -> bool {
+        env::storage_write(key.as_bytes(), value.as_bytes())
+    }
+
+    #[storage_tracked]
+    pub fn test_remove_tracked(&mut self, key: String) -> bool {
+        if !env::storage_has_key(key.as_bytes()) {
+            return false;
+        }
+
+        env::storage_remove(key.as_bytes())
+    }
+
+    #[storage_tracked]
+    pub fn test_register_tracked(&mut self, account_id: AccountId) {
+        self.insert_account(&account_id, Account::new(&account_id, None));
+    }
+}
+
+fn register_account(contract: &mut Contract, account_id: &AccountId, storage_balance: u128) {
+    contract.insert_account(account_id, Account::new(account_id, Some(storage_balance)));
+    contract.flush(); // flush content before use env::storage_usage()
+}
+
+fn storage_usage(contract: &Contract, account_id: &AccountId) -> u64 {
+    contract.get_account(account_id).unwrap().storage_usage
+}
+
+#[test]
+fn test_storage_tracked_matches_hand_tracked() {
+    let tracked_account_id = accounts(1);
+    let hand_tracked_account_id = accounts(2);
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &tracked_account_id, ONE_NEAR);
+    register_account(&mut contract, &hand_tracked_account_id, ONE_NEAR);
+
+    contract.test_write_tracked(
+        tracked_account_id.clone(),
+        "key1".to_owned(),
+        "value".to_owned(),
+    );
+    contract.test_write_hand_tracked(
+        hand_tracked_account_id.clone(),
+        "key2".to_owned(),
+        "value".to_owned(),
+    );
+
+    let tracked_storage_usage = storage_usage(&contract, &tracked_account_id)
+        - Account::initial_storage_usage(Some(&tracked_account_id));
+    let hand_tracked_storage_usage = storage_usage(&contract, &hand_tracked_account_id)
+        - Account::initial_storage_usage(Some(&hand_tracked_account_id));
+
+    assert!(tracked_storage_usage > 0);
+    assert_eq!(tracked_storage_usage, hand_tracked_storage_usage);
+}
+
+#[test]
+fn test_storage_tracked_optional_account_argument() {
+    let account_id = accounts(1);
+    let other_account_id = accounts(2);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+    register_account(&mut contract, &other_account_id, ONE_NEAR);
+
+    // omitted account falls back to predecessor
+    contract.test_write_optional_account(None, "key1".to_owned(), "value".to_owned());
+    contract.test_write_optional_account(
+        Some(other_account_id.clone()),
+        "key2".to_owned(),
+        "value".to_owned(),
+    );
+
+    assert_eq!(
+        storage_usage(&contract, &account_id) - Account::initial_storage_usage(Some(&account_id)),
+        storage_usage(&contract, &other_account_id)
+            - Account::initial_storage_usage(Some(&other_account_id))
+    );
+}
+
+#[test]
+fn test_storage_tracked_predecessor_with_early_return() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    contract.test_write_tracked(account_id.clone(), "key".to_owned(), "value".to_owned());
+
+    assert!(contract.test_remove_tracked("key".to_owned()));
+    assert!(!contract.test_remove_tracked("key".to_owned()));
+    assert_eq!(
+        storage_usage(&contract, &account_id),
+        Account::initial_storage_usage(Some(&account_id))
+    );
+}
+
+#[test]
+fn test_storage_tracked_flushes_collections() {
+    let account_id = accounts(1);
+    let other_account_id = accounts(2);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    contract.test_register_tracked(other_account_id.clone());
+
+    assert_eq!(
+        storage_usage(&contract, &account_id),
+        Account::initial_storage_usage(Some(&account_id))
+            + Account::initial_storage_usage(Some(&other_account_id))
+    );
+}
+
+#[test]
+#[should_panic = "Not enough storage balance to cover storage usage"]
+fn test_storage_tracked_not_enough_storage_balance() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(
+        &mut contract,
+        &account_id,
+        Account::required_deposit(Some(&account_id)).0,
+    );
+
+    contract.test_write_tracked(account_id, "key".to_owned(), "value".to_owned());
+}
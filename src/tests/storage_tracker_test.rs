@@ -0,0 +1,204 @@
+use super::super::account::{Account, AccountV1, VAccount};
+use super::super::Contract;
+use super::common::*;
+use crate::storage::{ACCOUNT_STORAGE, ACCOUNT_V1_STORAGE, STORAGE_ENTRY};
+use near_sdk::test_utils::accounts;
+use near_sdk::{env, testing_env, AccountId, StorageUsage, ONE_NEAR};
+
+const KEY: &[u8] = b"key";
+const VALUE: &[u8] = b"value";
+
+/// Storage used by a single raw entry with `KEY` and `VALUE`
+const ENTRY_STORAGE: StorageUsage = STORAGE_ENTRY + KEY.len() as u64 + VALUE.len() as u64;
+
+fn register_account(contract: &mut Contract, account_id: &AccountId, storage_balance: u128) {
+    contract.insert_account(account_id, Account::new(account_id, Some(storage_balance)));
+    contract.flush(); // flush content before use env::storage_usage()
+}
+
+#[test]
+fn test_track_storage_usage_charges_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    let result = contract.track_storage_usage(&account_id, |_| {
+        env::storage_write(KEY, VALUE);
+        42
+    });
+
+    assert_eq!(result, 42);
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id)) + ENTRY_STORAGE
+    );
+}
+
+#[test]
+fn test_track_storage_usage_refunds_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    contract.track_storage_usage(&account_id, |_| env::storage_write(KEY, VALUE));
+    contract.track_storage_usage(&account_id, |_| env::storage_remove(KEY));
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id))
+    );
+}
+
+#[test]
+fn test_try_track_storage_usage_not_covered() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    let required_deposit = Account::required_deposit(Some(&account_id)).0;
+    register_account(&mut contract, &account_id, required_deposit);
+
+    let (result, is_covered) = contract.try_track_storage_usage(&account_id, |_| {
+        env::storage_write(KEY, VALUE);
+        42
+    });
+
+    // usage is charged without panic, the caller reverts the change
+    assert_eq!(result, 42);
+    assert!(!is_covered);
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id)) + ENTRY_STORAGE
+    );
+
+    let (removed, is_covered) =
+        contract.try_track_storage_usage(&account_id, |_| env::storage_remove(KEY));
+
+    assert!(removed);
+
+    assert!(is_covered);
+}
+
+#[test]
+fn test_track_storage_usage_flushes_collections() {
+    let account_id = accounts(1);
+    let other_account_id = accounts(2);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    // pending collection changes are charged, even if not flushed by `f`
+    contract.track_storage_usage(&account_id, |contract| {
+        contract.insert_account(&other_account_id, Account::new(&other_account_id, None));
+    });
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id))
+            + Account::initial_storage_usage(Some(&other_account_id))
+    );
+}
+
+#[test]
+fn test_manual_storage_tracking() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(&mut contract, &account_id, ONE_NEAR);
+
+    let storage_tracker = contract.start_storage_tracking(&account_id);
+    env::storage_write(KEY, VALUE);
+    contract.finish_storage_tracking(&account_id, storage_tracker);
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id)) + ENTRY_STORAGE
+    );
+}
+
+#[test]
+fn test_track_storage_usage_legacy_account_upgrade_not_charged_twice() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    contract.accounts.insert(
+        account_id.clone(),
+        VAccount::V1(AccountV1 {
+            storage_balance: ONE_NEAR,
+            storage_usage: Account::initial_storage_usage(Some(&account_id))
+                - (ACCOUNT_STORAGE - ACCOUNT_V1_STORAGE)
+                - Account::account_ids_storage_usage(&account_id),
+        }),
+    );
+    contract.flush(); // flush content before use env::storage_usage()
+
+    contract.track_storage_usage(&account_id, |contract| {
+        contract
+            .get_account_mut(&account_id)
+            .unwrap()
+            .storage_balance += 1;
+    });
+
+    assert_eq!(
+        contract.get_account(&account_id).unwrap().storage_usage,
+        Account::initial_storage_usage(Some(&account_id))
+    );
+}
+
+#[test]
+#[should_panic = "Not enough storage balance to cover storage usage"]
+fn test_track_storage_usage_not_enough_storage_balance() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_account(
+        &mut contract,
+        &account_id,
+        Account::required_deposit(Some(&account_id)).0,
+    );
+
+    contract.track_storage_usage(&account_id, |_| env::storage_write(KEY, VALUE));
+}
+
+#[test]
+#[should_panic = "Account is not registered"]
+fn test_track_storage_usage_not_registered_account() {
+    let account_id = accounts(1);
+
+    let context = build_default_context(account_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.track_storage_usage(&account_id, |_| env::storage_write(KEY, VALUE));
+}
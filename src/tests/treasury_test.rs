@@ -0,0 +1,348 @@
+use super::super::Contract;
+use super::common::*;
+use crate::config::Config;
+use crate::roles::Role;
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::accounts;
+use near_sdk::{
+    testing_env, AccountId, Balance, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+};
+
+/// Deposit fee of 1%
+const FEE_BPS: u16 = 100;
+
+fn init_with_fee(config: Option<Config>) -> Contract {
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    Contract::init(
+        Some(accounts(0)),
+        None,
+        Some(config.unwrap_or(Config {
+            deposit_fee_bps: FEE_BPS,
+            ..Config::default()
+        })),
+    )
+}
+
+fn storage_deposit(
+    contract: &mut Contract,
+    account_id: AccountId,
+    amount: Balance,
+    registration_only: Option<bool>,
+) -> StorageBalance {
+    let context = build_default_context(account_id, Some(amount), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit(None, registration_only)
+}
+
+fn min_deposit(contract: &Contract, account_id: &AccountId) -> Balance {
+    contract.config.min_deposit(Some(account_id))
+}
+
+#[test]
+fn test_storage_deposit_fee_excludes_registration_minimum() {
+    let mut contract = init_with_fee(None);
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    let storage_balance = storage_deposit(&mut contract, accounts(1), min_deposit + ONE_NEAR, None);
+
+    assert_eq!(storage_balance.total.0, min_deposit + ONE_NEAR / 100 * 99);
+    assert_eq!(contract.treasury_balance(), U128(ONE_NEAR / 100));
+    assert_eq!(
+        get_events()[1],
+        json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "deposit_fee_collected",
+            "data": {
+                "account_id": "bob",
+                "amount": "10000000000000000000000",
+            }
+        })
+    );
+}
+
+#[test]
+fn test_storage_deposit_minimum_is_not_charged() {
+    let mut contract = init_with_fee(None);
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    let storage_balance = storage_deposit(&mut contract, accounts(1), min_deposit, None);
+
+    assert_eq!(storage_balance.total.0, min_deposit);
+    assert_eq!(contract.treasury_balance(), U128(0));
+    assert_eq!(get_events().len(), 1);
+}
+
+#[test]
+fn test_storage_deposit_registration_only_is_not_charged() {
+    let mut contract = init_with_fee(None);
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    let storage_balance = storage_deposit(&mut contract, accounts(1), ONE_NEAR, Some(true));
+
+    assert_eq!(storage_balance.total.0, min_deposit);
+    assert_eq!(contract.treasury_balance(), U128(0));
+    assert_eq!(get_transfers(), vec![(accounts(1), ONE_NEAR - min_deposit)]);
+}
+
+#[test]
+fn test_storage_deposit_fee_on_top_up() {
+    let mut contract = init_with_fee(None);
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    storage_deposit(&mut contract, accounts(1), min_deposit, None);
+    let account = contract.get_account(&accounts(1)).unwrap();
+
+    let storage_balance = storage_deposit(&mut contract, accounts(1), ONE_NEAR, None);
+
+    assert_eq!(storage_balance.total.0, min_deposit + ONE_NEAR / 100 * 99);
+    assert_eq!(contract.treasury_balance(), U128(ONE_NEAR / 100));
+
+    // fee isn't charged on storage used by the account
+    let topped_up_account = contract.get_account(&accounts(1)).unwrap();
+    assert_eq!(topped_up_account.storage_usage, account.storage_usage);
+    assert_eq!(topped_up_account.locked_balance(), account.locked_balance());
+}
+
+#[test]
+fn test_storage_deposit_fee_excludes_refund() {
+    let max_storage_balance = 2 * ONE_NEAR;
+    let mut contract = init_with_fee(Some(Config {
+        max_storage_balance: Some(U128(max_storage_balance)),
+        deposit_fee_bps: FEE_BPS,
+        ..Config::default()
+    }));
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    let storage_balance = storage_deposit(&mut contract, accounts(1), 3 * ONE_NEAR, None);
+
+    let fee = (max_storage_balance - min_deposit) / 100;
+    assert_eq!(storage_balance.total.0, max_storage_balance - fee);
+    assert_eq!(contract.treasury_balance(), U128(fee));
+    assert_eq!(get_transfers(), vec![(accounts(1), ONE_NEAR)]);
+}
+
+#[test]
+fn test_storage_deposit_batch_fee_excludes_registration_minimums() {
+    let mut contract = init_with_fee(None);
+    let account_ids = vec![accounts(1), accounts(2)];
+    let min_deposits: Vec<Balance> = account_ids
+        .iter()
+        .map(|account_id| min_deposit(&contract, account_id))
+        .collect();
+
+    let deposit = min_deposits.iter().sum::<Balance>() + 2 * ONE_NEAR;
+    let context = build_default_context(accounts(0), Some(deposit), None);
+
+    testing_env!(context.build());
+    let results = contract.storage_deposit_batch(account_ids, None);
+
+    for (result, min_deposit) in results.iter().zip(&min_deposits) {
+        assert_eq!(
+            result.storage_balance.as_ref().unwrap().total.0,
+            min_deposit + ONE_NEAR / 100 * 99
+        );
+    }
+    assert_eq!(contract.treasury_balance(), U128(2 * ONE_NEAR / 100));
+    assert_eq!(get_transfers(), vec![]);
+    assert_eq!(
+        get_events()
+            .iter()
+            .filter(|event| event["event"] == "deposit_fee_collected")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_storage_deposit_batch_registration_only_is_not_charged() {
+    let mut contract = init_with_fee(None);
+
+    let context = build_default_context(accounts(0), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    contract.storage_deposit_batch(vec![accounts(1)], Some(true));
+
+    assert_eq!(contract.treasury_balance(), U128(0));
+    assert_eq!(
+        get_transfers(),
+        vec![(accounts(0), ONE_NEAR - min_deposit(&contract, &accounts(1)))]
+    );
+}
+
+#[test]
+fn test_storage_sponsor_fee_excludes_registration_minimum() {
+    let mut contract = init_with_fee(None);
+
+    let context = build_default_context(accounts(2), Some(2 * ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_sponsor(accounts(1));
+
+    let account = contract.get_account(&accounts(1)).unwrap();
+    let min_deposit = min_deposit(&contract, &accounts(1)).max(account.locked_balance());
+    let fee = (2 * ONE_NEAR - min_deposit) / 100;
+
+    assert_eq!(storage_balance.total.0, 2 * ONE_NEAR - fee);
+    assert_eq!(account.sponsored_balance, 2 * ONE_NEAR - fee);
+    assert_eq!(contract.treasury_balance(), U128(fee));
+    assert_eq!(get_events()[1]["event"], "deposit_fee_collected");
+}
+
+#[test]
+fn test_storage_sponsor_fee_on_top_up() {
+    let mut contract = init_with_fee(None);
+    let min_deposit = min_deposit(&contract, &accounts(1));
+
+    storage_deposit(&mut contract, accounts(1), min_deposit, None);
+
+    let context = build_default_context(accounts(2), Some(ONE_NEAR), None);
+
+    testing_env!(context.build());
+    let storage_balance = contract.storage_sponsor(accounts(1));
+
+    assert_eq!(storage_balance.total.0, min_deposit + ONE_NEAR / 100 * 99);
+    assert_eq!(contract.treasury_balance(), U128(ONE_NEAR / 100));
+}
+
+#[test]
+fn test_token_deposit_is_not_charged() {
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+
+    let token_id: AccountId = "usdc.near".parse().unwrap();
+    let rate = 1_000_000_000_000_000_000;
+
+    let mut contract = init_with_fee(None);
+    contract.set_storage_token(token_id.clone(), Some(U128(rate)));
+
+    let context = build_default_context(token_id, None, None);
+
+    testing_env!(context.build());
+    contract.ft_on_transfer(accounts(1), U128(10_000), String::new());
+
+    assert_eq!(
+        contract.storage_balance_of(accounts(1)).unwrap().total.0,
+        10_000 * rate
+    );
+    assert_eq!(contract.treasury_balance(), U128(0));
+}
+
+#[test]
+fn test_deposit_fees_are_accounted_separately() {
+    let mut contract = init_with_fee(None);
+
+    let mut deposited = 0;
+    for (account_id, amount) in [
+        (accounts(1), ONE_NEAR),
+        (accounts(2), 3 * ONE_NEAR),
+        (accounts(1), ONE_NEAR / 3),
+    ] {
+        storage_deposit(&mut contract, account_id, amount, None);
+        deposited += amount;
+    }
+
+    let storage_balances: Balance = [accounts(1), accounts(2)]
+        .into_iter()
+        .map(|account_id| contract.storage_balance_of(account_id).unwrap().total.0)
+        .sum();
+    assert!(contract.treasury_balance().0 > 0);
+    assert_eq!(storage_balances + contract.treasury_balance().0, deposited);
+}
+
+#[test]
+fn test_withdraw_treasury() {
+    let mut contract = init_with_fee(None);
+    contract.grant_role(Role::Treasurer, accounts(3));
+
+    storage_deposit(&mut contract, accounts(1), 2 * ONE_NEAR, None);
+    let storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    let fee = contract.treasury_balance();
+
+    let context = build_default_context(accounts(3), Some(1), None);
+
+    testing_env!(context.build());
+    contract.withdraw_treasury(fee, accounts(4));
+
+    assert_eq!(contract.treasury_balance(), U128(0));
+    assert_eq!(get_transfers(), vec![(accounts(4), fee.0)]);
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "treasury_withdraw",
+            "data": {
+                "receiver_id": "eugene",
+                "amount": fee,
+            }
+        })]
+    );
+
+    // storage balance backing the account isn't affected
+    let withdrawn_storage_balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert_eq!(withdrawn_storage_balance.total, storage_balance.total);
+    assert_eq!(
+        withdrawn_storage_balance.available,
+        storage_balance.available
+    );
+}
+
+#[test]
+#[should_panic = "Not enough treasury balance"]
+fn test_withdraw_treasury_exceeds_balance() {
+    let mut contract = init_with_fee(None);
+
+    storage_deposit(&mut contract, accounts(1), 2 * ONE_NEAR, None);
+    let fee = contract.treasury_balance();
+
+    // storage balances of accounts aren't withdrawable as fees
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.withdraw_treasury(U128(fee.0 + 1), accounts(0));
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_withdraw_treasury_not_treasurer() {
+    let mut contract = init_with_fee(None);
+
+    storage_deposit(&mut contract, accounts(1), 2 * ONE_NEAR, None);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    contract.withdraw_treasury(U128(1), accounts(1));
+}
+
+#[test]
+fn test_on_treasury_withdraw_rollback() {
+    let mut contract = init_with_fee(None);
+
+    storage_deposit(&mut contract, accounts(1), 2 * ONE_NEAR, None);
+    let fee = contract.treasury_balance();
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    testing_env!(context.build());
+    contract.withdraw_treasury(fee, accounts(4));
+
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(
+        context.build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    contract.on_treasury_withdraw(accounts(4), fee);
+
+    assert_eq!(contract.treasury_balance(), fee);
+    assert_eq!(get_events()[0]["event"], "treasury_withdraw_rollback");
+}
@@ -0,0 +1,452 @@
+use super::super::account::Account;
+use super::super::Contract;
+use super::common::*;
+use crate::misc::{Hash, RunningState};
+use crate::roles::Role;
+use crate::storage::StorageKey;
+use crate::upgrade::{
+    stage_code_from_input, OldContract, DEFAULT_UPGRADE_DELAY, MIN_UPGRADE_DELAY, STATE_FEATURES,
+    STATE_VERSION,
+};
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde_json::json;
+use near_sdk::store::LookupMap;
+use near_sdk::test_utils::{accounts, get_created_receipts};
+use near_sdk::{env, testing_env, CryptoHash, ONE_NEAR};
+
+const CODE: &[u8] = b"\0asm staged code";
+
+fn staged_code_key() -> Vec<u8> {
+    StorageKey::StagedCode.try_to_vec().unwrap()
+}
+
+fn state_version_key() -> Vec<u8> {
+    StorageKey::StateVersion.try_to_vec().unwrap()
+}
+
+#[test]
+fn test_migrate_from_previous_layout() {
+    let owner_id = accounts(0);
+    let account_id = accounts(1);
+
+    let context = build_default_context(owner_id.clone(), None, None);
+
+    testing_env!(context.build());
+
+    let mut accounts_map = LookupMap::new(StorageKey::Accounts);
+    accounts_map.insert(
+        account_id.clone(),
+        Account::new(&account_id, Some(ONE_NEAR)).into(),
+    );
+    accounts_map.flush(); // flush content before state migration
+
+    env::state_write(&OldContract {
+        owner_id: owner_id.clone(),
+        running_state: RunningState::Paused,
+        accounts: accounts_map,
+    });
+
+    let mut contract = Contract::migrate();
+
+    assert_eq!(contract.owner_id(), owner_id);
+    assert_eq!(contract.pending_owner_id(), None);
+    assert_eq!(contract.get_running_state(), RunningState::Paused);
+    assert!(!contract.has_role(Role::Upgrader, account_id.clone()));
+
+    // accounts survive migration
+    contract.running_state = RunningState::Running;
+    let storage_balance = contract.storage_balance_of(account_id.clone()).unwrap();
+
+    assert_eq!(storage_balance.total.0, ONE_NEAR);
+    assert_eq!(
+        storage_balance.available.0,
+        ONE_NEAR - Account::required_deposit(Some(&account_id)).0
+    );
+    assert_eq!(
+        env::storage_read(&state_version_key()),
+        Some(vec![STATE_VERSION, STATE_FEATURES])
+    );
+}
+
+#[test]
+fn test_migrate_from_current_layout() {
+    let owner_id = accounts(0);
+    let upgrader_id = accounts(1);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    testing_env!(context.build());
+    let mut contract = Contract::init(Some(owner_id.clone()), None, None);
+    contract.grant_role(Role::Upgrader, upgrader_id.clone());
+    contract.upgrade_delay = 2 * DEFAULT_UPGRADE_DELAY;
+    contract.treasury_balance = ONE_NEAR;
+    contract.flush(); // flush content before state migration
+    env::state_write(&contract);
+
+    assert_eq!(
+        env::storage_read(&state_version_key()),
+        Some(vec![STATE_VERSION, STATE_FEATURES])
+    );
+
+    // same layout is kept as is
+    let contract = Contract::migrate();
+
+    assert_eq!(contract.owner_id(), owner_id);
+    assert!(contract.has_role(Role::Upgrader, upgrader_id));
+    assert_eq!(contract.upgrade_delay, 2 * DEFAULT_UPGRADE_DELAY);
+    assert_eq!(contract.treasury_balance().0, ONE_NEAR);
+    assert_eq!(
+        env::storage_read(&state_version_key()),
+        Some(vec![STATE_VERSION, STATE_FEATURES])
+    );
+}
+
+#[test]
+#[should_panic = "Unsupported state version"]
+fn test_migrate_unsupported_state_version() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+    let contract = Contract::init(Some(accounts(0)), None, None);
+    env::state_write(&contract);
+    env::storage_write(&state_version_key(), &[STATE_VERSION + 1, STATE_FEATURES]);
+
+    Contract::migrate();
+}
+
+#[test]
+#[should_panic = "State was written with another set of token features"]
+fn test_migrate_state_of_other_features() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+    let contract = Contract::init(Some(accounts(0)), None, None);
+    env::state_write(&contract);
+    // state of a build with toggled `ft` feature
+    env::storage_write(&state_version_key(), &[STATE_VERSION, STATE_FEATURES ^ 1]);
+
+    Contract::migrate();
+}
+
+#[test]
+#[should_panic = "Contract is not initialized"]
+fn test_migrate_not_initialized_contract() {
+    let context = build_default_context(accounts(0), None, None);
+
+    testing_env!(context.build());
+    Contract::migrate();
+}
+
+#[test]
+fn test_stage_code() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    contract.stage_code(CODE.to_vec());
+
+    let staged_code_info = contract.staged_code_info().unwrap();
+    let hash: CryptoHash = staged_code_info.hash.into();
+
+    assert_eq!(hash.to_vec(), CODE.to_vec().hash());
+    assert_eq!(
+        staged_code_info.unlock_timestamp.0,
+        1_000 + DEFAULT_UPGRADE_DELAY
+    );
+    assert_eq!(
+        env::storage_read(&staged_code_key()),
+        Some(CODE.to_vec().try_to_vec().unwrap())
+    );
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_stage_code_not_upgrader() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.stage_code(CODE.to_vec());
+}
+
+#[test]
+fn test_upgrader_stages_code() {
+    let upgrader_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Upgrader, upgrader_id.clone());
+
+    let context = build_default_context(upgrader_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.stage_code(CODE.to_vec());
+
+    assert!(contract.staged_code_info().is_some());
+}
+
+#[test]
+#[should_panic = "Staged code is locked"]
+fn test_deploy_staged_code_before_unlock() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    contract.stage_code(CODE.to_vec());
+
+    testing_env!(context
+        .block_timestamp(1_000 + DEFAULT_UPGRADE_DELAY - 1)
+        .build());
+    contract.deploy_staged_code();
+}
+
+#[test]
+fn test_deploy_staged_code_after_unlock() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    contract.stage_code(CODE.to_vec());
+
+    testing_env!(context
+        .block_timestamp(1_000 + DEFAULT_UPGRADE_DELAY)
+        .build());
+    contract.deploy_staged_code();
+
+    // staged code is cleared and its storage released
+    assert!(contract.staged_code_info().is_none());
+    assert_eq!(env::storage_read(&staged_code_key()), None);
+}
+
+#[test]
+#[should_panic = "No staged code"]
+fn test_deploy_without_staged_code() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.deploy_staged_code();
+}
+
+#[test]
+fn test_cancel_staged_code() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    let storage_usage = env::storage_usage();
+
+    contract.stage_code(CODE.to_vec());
+    assert!(env::storage_usage() > storage_usage);
+
+    contract.cancel_staged_code();
+
+    assert!(contract.staged_code_info().is_none());
+    assert_eq!(env::storage_read(&staged_code_key()), None);
+    assert_eq!(env::storage_usage(), storage_usage);
+}
+
+#[test]
+fn test_set_upgrade_delay() {
+    let owner_id = accounts(0);
+    let upgrade_delay = 2 * DEFAULT_UPGRADE_DELAY;
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    contract.set_upgrade_delay(upgrade_delay.into());
+    contract.stage_code(CODE.to_vec());
+
+    // raised delay takes effect immediately
+    assert_eq!(contract.upgrade_delay().0, upgrade_delay);
+    assert!(contract.pending_upgrade_delay().is_none());
+    assert_eq!(
+        contract.staged_code_info().unwrap().unlock_timestamp.0,
+        1_000 + upgrade_delay
+    );
+}
+
+#[test]
+fn test_lowered_upgrade_delay_waits_current_delay() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    contract.set_upgrade_delay(MIN_UPGRADE_DELAY.into());
+
+    assert_eq!(contract.upgrade_delay().0, DEFAULT_UPGRADE_DELAY);
+    let pending_upgrade_delay = contract.pending_upgrade_delay().unwrap();
+    assert_eq!(pending_upgrade_delay.upgrade_delay.0, MIN_UPGRADE_DELAY);
+    assert_eq!(
+        pending_upgrade_delay.unlock_timestamp.0,
+        1_000 + DEFAULT_UPGRADE_DELAY
+    );
+    assert_eq!(
+        get_events(),
+        vec![json!({
+            "standard": "template-contract",
+            "version": "1.0.0",
+            "event": "upgrade_delay_updated",
+            "data": {
+                "upgrade_delay": MIN_UPGRADE_DELAY.to_string(),
+                "unlock_timestamp": (1_000 + DEFAULT_UPGRADE_DELAY).to_string(),
+            }
+        })]
+    );
+
+    // code staged before the lowered delay takes effect is locked for the current delay
+    contract.stage_code(CODE.to_vec());
+    assert_eq!(
+        contract.staged_code_info().unwrap().unlock_timestamp.0,
+        1_000 + DEFAULT_UPGRADE_DELAY
+    );
+
+    let timestamp = 1_000 + DEFAULT_UPGRADE_DELAY;
+    testing_env!(context.block_timestamp(timestamp).build());
+    contract.stage_code(CODE.to_vec());
+
+    assert_eq!(contract.upgrade_delay().0, MIN_UPGRADE_DELAY);
+    assert!(contract.pending_upgrade_delay().is_none());
+    assert_eq!(
+        contract.staged_code_info().unwrap().unlock_timestamp.0,
+        timestamp + MIN_UPGRADE_DELAY
+    );
+}
+
+#[test]
+#[should_panic = "Upgrade delay is less than minimum"]
+fn test_set_upgrade_delay_below_minimum() {
+    let owner_id = accounts(0);
+
+    let context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.build());
+    contract.set_upgrade_delay((MIN_UPGRADE_DELAY - 1).into());
+}
+
+#[test]
+fn test_staged_code_events() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    let mut contract = Contract::init(Some(owner_id), None, None);
+
+    testing_env!(context.block_timestamp(0).build());
+    contract.stage_code(CODE.to_vec());
+    contract.cancel_staged_code();
+
+    let hash = String::from(&Base58CryptoHash::from(
+        CryptoHash::try_from(CODE.to_vec().hash()).unwrap(),
+    ));
+
+    assert_eq!(
+        near_sdk::test_utils::get_logs(),
+        vec![
+            format!(
+                r#"EVENT_JSON:{{"standard":"template-contract","version":"1.0.0","event":"code_staged","data":{{"hash":"{}","unlock_timestamp":"{}"}}}}"#,
+                hash, DEFAULT_UPGRADE_DELAY
+            ),
+            format!(
+                r#"EVENT_JSON:{{"standard":"template-contract","version":"1.0.0","event":"staged_code_cancelled","data":{{"hash":"{}"}}}}"#,
+                hash
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_upgrade_entrypoint_stages_code() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    env::state_write(&Contract::init(Some(owner_id), None, None));
+
+    testing_env!(with_input(&context, CODE));
+    stage_code_from_input();
+
+    // code isn't deployed right away
+    assert!(get_created_receipts().is_empty());
+
+    let contract: Contract = env::state_read().unwrap();
+    assert_eq!(
+        contract.staged_code_info().unwrap().unlock_timestamp.0,
+        1_000 + DEFAULT_UPGRADE_DELAY
+    );
+}
+
+#[test]
+#[should_panic = "Staged code is locked"]
+fn test_upgrade_entrypoint_instant_deploy() {
+    let owner_id = accounts(0);
+
+    let mut context = build_default_context(owner_id.clone(), Some(1), None);
+
+    testing_env!(context.block_timestamp(1_000).build());
+    env::state_write(&Contract::init(Some(owner_id), None, None));
+
+    testing_env!(with_input(&context, CODE));
+    stage_code_from_input();
+
+    let mut contract: Contract = env::state_read().unwrap();
+    contract.deploy_staged_code();
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_upgrade_entrypoint_not_upgrader() {
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    env::state_write(&Contract::init(Some(accounts(0)), None, None));
+
+    testing_env!(with_input(&context, CODE));
+    stage_code_from_input();
+}
+
+#[test]
+#[should_panic = "Not allowed"]
+fn test_set_upgrade_delay_not_owner() {
+    let upgrader_id = accounts(1);
+
+    let context = build_default_context(accounts(0), Some(1), None);
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+
+    testing_env!(context.build());
+    contract.grant_role(Role::Upgrader, upgrader_id.clone());
+
+    let context = build_default_context(upgrader_id, Some(1), None);
+
+    testing_env!(context.build());
+    contract.set_upgrade_delay(MIN_UPGRADE_DELAY.into());
+}
@@ -0,0 +1,100 @@
+use super::super::Contract;
+use super::common::*;
+use crate::views::MAX_PAGE_SIZE;
+use near_contract_standards::storage_management::*;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::accounts;
+use near_sdk::{testing_env, AccountId, ONE_NEAR};
+
+fn register_accounts(contract: &mut Contract, account_ids: &[AccountId]) {
+    for account_id in account_ids {
+        let context = build_default_context(account_id.clone(), Some(ONE_NEAR), None);
+
+        testing_env!(context.build());
+        contract.storage_deposit(None, None);
+    }
+}
+
+#[test]
+fn test_get_accounts() {
+    let account_ids = [accounts(1), accounts(2), accounts(3)];
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_accounts(&mut contract, &account_ids);
+
+    assert_eq!(contract.accounts_count(), 3);
+
+    let result = contract.get_accounts(None, None);
+    assert_eq!(
+        result
+            .iter()
+            .map(|account| account.account_id.clone())
+            .collect::<Vec<_>>(),
+        account_ids
+    );
+    assert!(result
+        .iter()
+        .all(|account| account.storage_balance.total.0 == ONE_NEAR));
+
+    let result = contract.get_accounts(Some(U128(1)), Some(1));
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].account_id, accounts(2));
+
+    assert!(contract.get_accounts(Some(U128(3)), None).is_empty());
+}
+
+#[test]
+fn test_unregistered_account_removed_from_index() {
+    let account_ids = [accounts(1), accounts(2)];
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_accounts(&mut contract, &account_ids);
+
+    let context = build_default_context(accounts(1), Some(1), None);
+
+    testing_env!(context.build());
+    assert!(contract.storage_unregister(Some(true)));
+
+    assert_eq!(contract.accounts_count(), 1);
+    assert_eq!(contract.get_accounts(None, None)[0].account_id, accounts(2));
+}
+
+#[test]
+fn test_get_accounts_page_size_cap() {
+    let account_ids = (0..=MAX_PAGE_SIZE)
+        .map(|index| format!("account{}.near", index).parse().unwrap())
+        .collect::<Vec<AccountId>>();
+
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_accounts(&mut contract, &account_ids);
+
+    assert_eq!(
+        contract.get_accounts(None, None).len() as u64,
+        MAX_PAGE_SIZE
+    );
+    assert_eq!(
+        contract.get_accounts(None, Some(u64::MAX)).len() as u64,
+        MAX_PAGE_SIZE
+    );
+
+    let result = contract.get_accounts(Some(U128(MAX_PAGE_SIZE.into())), None);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].account_id, account_ids[MAX_PAGE_SIZE as usize]);
+}
+
+#[test]
+#[should_panic = "Out of bounds, please use a smaller from_index."]
+fn test_get_accounts_out_of_bounds() {
+    let mut contract = Contract::init(Some(accounts(0)), None, None);
+    register_accounts(&mut contract, &[accounts(1)]);
+
+    contract.get_accounts(Some(U128(2)), None);
+}
+
+#[test]
+#[should_panic = "Cannot provide limit of 0."]
+fn test_get_accounts_zero_limit() {
+    let contract = Contract::init(Some(accounts(0)), None, None);
+
+    contract.get_accounts(None, Some(0));
+}
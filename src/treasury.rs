@@ -0,0 +1,90 @@
+use crate::events::Event;
+use crate::roles::Role;
+use crate::{Contract, ContractExt};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, require, AccountId, Balance, Gas,
+    Promise,
+};
+
+/// Gas reserved for the callback, which restores treasury balance if transfer to receiver failed
+const GAS_FOR_ON_TREASURY_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+#[near_bindgen]
+impl Contract {
+    /// Returns deposit fees collected by the contract in $NEAR
+    ///
+    /// Treasury balance is accounted separately from storage balances of accounts
+    pub fn treasury_balance(&self) -> U128 {
+        self.treasury_balance.into()
+    }
+
+    /// Transfers `amount` of collected fees to `receiver_id`, restored to the treasury if the transfer fails
+    ///
+    /// Storage balances of accounts are never withdrawn
+    #[payable]
+    pub fn withdraw_treasury(&mut self, amount: U128, receiver_id: AccountId) {
+        assert_one_yocto();
+        self.assert_role(Role::Treasurer);
+
+        require!(
+            amount.0 > 0 && amount.0 <= self.treasury_balance,
+            "Not enough treasury balance"
+        );
+
+        self.treasury_balance -= amount.0;
+
+        Event::TreasuryWithdraw {
+            receiver_id: &receiver_id,
+            amount,
+        }
+        .emit();
+
+        Promise::new(receiver_id.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_TREASURY_TRANSFER)
+                .on_treasury_withdraw(receiver_id, amount),
+        );
+    }
+
+    /// Callback of `withdraw_treasury` transfer, restores withdrawn `amount` if the transfer failed
+    #[private]
+    pub fn on_treasury_withdraw(&mut self, receiver_id: AccountId, amount: U128) {
+        if is_promise_success() {
+            return;
+        }
+
+        self.add_to_treasury(amount.0);
+
+        Event::TreasuryWithdrawRollback {
+            receiver_id: &receiver_id,
+            amount,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Adds deposit `fee` charged on storage deposit of `account_id` to the treasury
+    pub(crate) fn collect_deposit_fee(&mut self, account_id: &AccountId, fee: Balance) {
+        if fee == 0 {
+            return;
+        }
+
+        self.add_to_treasury(fee);
+
+        Event::DepositFeeCollected {
+            account_id,
+            amount: fee.into(),
+        }
+        .emit();
+    }
+
+    /// Adds `amount` to the treasury balance
+    pub(crate) fn add_to_treasury(&mut self, amount: Balance) {
+        self.treasury_balance = self
+            .treasury_balance
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Treasury balance overflow"));
+    }
+}
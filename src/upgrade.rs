@@ -0,0 +1,386 @@
+use crate::account::VAccount;
+use crate::config::Config;
+use crate::events::Event;
+#[cfg(feature = "ft")]
+use crate::fungible_token::FungibleToken;
+use crate::indexed_set::IndexedSet;
+use crate::misc::{Hash, PausedMethods, RunningState};
+#[cfg(feature = "mt")]
+use crate::multi_token::MultiToken;
+#[cfg(feature = "nft")]
+use crate::non_fungible_token::NonFungibleTokens;
+use crate::registration_policy::RegistrationPolicy;
+use crate::roles::Role;
+use crate::source_metadata::ContractSourceMetadata;
+use crate::storage::StorageKey;
+use crate::{Contract, ContractExt};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+use near_sdk::json_types::{Base58CryptoHash, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::store::{LookupMap, UnorderedMap};
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, require, AccountId, CryptoHash, Duration, Gas,
+    IntoStorageKey, Promise,
+};
+
+/// Gas reserved for the upgrading method execution, the rest is attached to `migrate` call
+const GAS_FOR_UPGRADE: Gas = Gas(20_000_000_000_000);
+
+/// Default delay between staging code and deploying it [1 day]
+pub(crate) const DEFAULT_UPGRADE_DELAY: Duration = 24 * 60 * 60 * 1_000_000_000;
+
+/// Minimum delay between staging code and deploying it, which gives users time to exit [12 hours]
+pub(crate) const MIN_UPGRADE_DELAY: Duration = 12 * 60 * 60 * 1_000_000_000;
+
+/// Version of the contract state layout, stored apart from the state under `StorageKey::StateVersion`
+///
+/// Has to be bumped with every change of `Contract` layout along with a migration branch in `migrate`
+pub(crate) const STATE_VERSION: u8 = 1;
+
+/// Token features the contract is built with, stored along with `STATE_VERSION`
+///
+/// `Contract` layout depends on enabled `ft`, `nft` and `mt` features, so state written with another
+/// set of features can't be read
+pub(crate) const STATE_FEATURES: u8 = cfg!(feature = "ft") as u8
+    | (cfg!(feature = "nft") as u8) << 1
+    | (cfg!(feature = "mt") as u8) << 2;
+
+/// Staged code commitment
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedCodeInfo {
+    /// sha256 hash of the staged code
+    pub hash: Base58CryptoHash,
+    /// Timestamp in nanoseconds after which the staged code could be deployed
+    pub unlock_timestamp: U64,
+}
+
+/// Lowered upgrade delay, which takes effect after the current delay
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingUpgradeDelay {
+    /// Delay in nanoseconds between staging code and deploying it
+    pub upgrade_delay: U64,
+    /// Timestamp in nanoseconds after which the delay takes effect
+    pub unlock_timestamp: U64,
+}
+
+/// Contract state layout of the previous release (v0.1.0)
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct OldContract {
+    pub(crate) owner_id: AccountId,
+    pub(crate) running_state: RunningState,
+    pub(crate) accounts: LookupMap<AccountId, VAccount>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Migrates contract state from the layout of its state version, the current layout is kept as is
+    ///
+    /// State of the current version written with another set of token features is rejected
+    ///
+    /// Called by `deploy_staged_code` in the same batch with the new code deployment
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let contract = match read_state_version() {
+            // state of v0.1.0 has no version
+            None => Self::migrate_from_v0_1_0(),
+            Some((STATE_VERSION, STATE_FEATURES)) => {
+                env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"))
+            }
+            Some((STATE_VERSION, _)) => env::panic_str(
+                "State was written with another set of token features, build the contract with the same `ft`, `nft` and `mt` features",
+            ),
+            Some(_) => env::panic_str("Unsupported state version"),
+        };
+
+        write_state_version();
+
+        contract
+    }
+
+    /// Stages new contract code, which could be deployed by `deploy_staged_code` after `upgrade_delay`
+    ///
+    /// Replaces previously staged code, if any
+    #[payable]
+    pub fn stage_code(&mut self, #[serializer(borsh)] code: Vec<u8>) {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+
+        require!(!code.is_empty(), "Contract code is not provided");
+
+        self.apply_pending_upgrade_delay();
+
+        let hash = CryptoHash::try_from(code.hash())
+            .unwrap_or_else(|_| env::panic_str("Invalid code hash"))
+            .into();
+        let unlock_timestamp = env::block_timestamp()
+            .checked_add(self.upgrade_delay)
+            .unwrap_or_else(|| env::panic_str("Unlock timestamp overflow"))
+            .into();
+
+        self.staged_code.set(&code);
+
+        let staged_code_info = StagedCodeInfo {
+            hash,
+            unlock_timestamp,
+        };
+
+        Event::CodeStaged {
+            hash: &staged_code_info.hash,
+            unlock_timestamp: &staged_code_info.unlock_timestamp,
+        }
+        .emit();
+
+        self.staged_code_info = Some(staged_code_info);
+    }
+
+    /// Deploys staged code and calls `migrate` on it, available only after unlock timestamp
+    #[payable]
+    pub fn deploy_staged_code(&mut self) -> Promise {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+
+        let staged_code_info = self
+            .staged_code_info
+            .take()
+            .unwrap_or_else(|| env::panic_str("No staged code"));
+
+        require!(
+            env::block_timestamp() >= staged_code_info.unlock_timestamp.0,
+            "Staged code is locked"
+        );
+
+        // take the code out of storage to release it
+        let code = self
+            .staged_code
+            .take()
+            .unwrap_or_else(|| env::panic_str("No staged code"));
+
+        Event::StagedCodeDeployed {
+            hash: &staged_code_info.hash,
+        }
+        .emit();
+
+        deploy_and_migrate(code)
+    }
+
+    /// Cancels staged code and releases its storage
+    #[payable]
+    pub fn cancel_staged_code(&mut self) {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+
+        let staged_code_info = self
+            .staged_code_info
+            .take()
+            .unwrap_or_else(|| env::panic_str("No staged code"));
+
+        self.staged_code.remove();
+
+        Event::StagedCodeCancelled {
+            hash: &staged_code_info.hash,
+        }
+        .emit();
+    }
+
+    /// Updates delay in nanoseconds between staging code and deploying it, not less than `MIN_UPGRADE_DELAY`
+    ///
+    /// Applies to code staged after the update. Lowered delay takes effect only after the current delay,
+    /// so users keep the announced time to exit
+    #[payable]
+    pub fn set_upgrade_delay(&mut self, upgrade_delay: U64) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            upgrade_delay.0 >= MIN_UPGRADE_DELAY,
+            "Upgrade delay is less than minimum"
+        );
+
+        self.apply_pending_upgrade_delay();
+
+        let unlock_timestamp = if upgrade_delay.0 < self.upgrade_delay {
+            let unlock_timestamp = env::block_timestamp()
+                .checked_add(self.upgrade_delay)
+                .unwrap_or_else(|| env::panic_str("Unlock timestamp overflow"))
+                .into();
+
+            self.pending_upgrade_delay = Some(PendingUpgradeDelay {
+                upgrade_delay,
+                unlock_timestamp,
+            });
+
+            unlock_timestamp
+        } else {
+            self.upgrade_delay = upgrade_delay.0;
+            self.pending_upgrade_delay = None;
+
+            env::block_timestamp().into()
+        };
+
+        Event::UpgradeDelayUpdated {
+            upgrade_delay,
+            unlock_timestamp,
+        }
+        .emit();
+    }
+
+    /// Returns hash and unlock timestamp of the staged code, if any
+    pub fn staged_code_info(&self) -> Option<StagedCodeInfo> {
+        self.staged_code_info.clone()
+    }
+
+    /// Returns delay in nanoseconds between staging code and deploying it
+    pub fn upgrade_delay(&self) -> U64 {
+        match &self.pending_upgrade_delay {
+            Some(pending) if env::block_timestamp() >= pending.unlock_timestamp.0 => {
+                pending.upgrade_delay
+            }
+            _ => self.upgrade_delay.into(),
+        }
+    }
+
+    /// Returns lowered upgrade delay and timestamp after which it takes effect, if any
+    pub fn pending_upgrade_delay(&self) -> Option<PendingUpgradeDelay> {
+        self.pending_upgrade_delay
+            .clone()
+            .filter(|pending| env::block_timestamp() < pending.unlock_timestamp.0)
+    }
+
+    /// Indexes accounts registered before `account_ids` index was introduced, returns number of newly indexed accounts
+    ///
+    /// Such accounts are otherwise indexed only upon write access to their data, so the owner backfills the index
+    /// after `migrate` in batches of `account_ids` sized to fit the gas limit.
+    /// Unregistered and already indexed accounts are skipped
+    #[payable]
+    pub fn index_legacy_accounts(&mut self, account_ids: Vec<AccountId>) -> u32 {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let mut indexed = 0;
+        for account_id in &account_ids {
+            if !self.account_ids.contains(account_id) && self.get_account_mut(account_id).is_ok() {
+                indexed += 1;
+            }
+        }
+
+        indexed
+    }
+}
+
+impl Contract {
+    /// Migrates contract state from the layout of v0.1.0
+    fn migrate_from_v0_1_0() -> Self {
+        let old_state: OldContract =
+            env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"));
+        #[cfg(feature = "nft")]
+        let nft = NonFungibleTokens::new(old_state.owner_id.clone());
+
+        Self {
+            owner_id: old_state.owner_id,
+            pending_owner_id: None,
+            running_state: old_state.running_state,
+            paused_methods: PausedMethods::default(),
+            accounts: old_state.accounts,
+            account_ids: IndexedSet::new(StorageKey::AccountIds),
+            roles: LookupMap::new(StorageKey::Roles),
+            registration_policy: RegistrationPolicy::default(),
+            allow_list: IndexedSet::new(StorageKey::AllowList),
+            deny_list: IndexedSet::new(StorageKey::DenyList),
+            storage_tokens: UnorderedMap::new(StorageKey::StorageTokens),
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_code_info: None,
+            upgrade_delay: DEFAULT_UPGRADE_DELAY,
+            pending_upgrade_delay: None,
+            source_metadata: ContractSourceMetadata::new(None),
+            config: Config::default(),
+            treasury_balance: 0,
+            refunds: LookupMap::new(StorageKey::Refunds),
+            token_refunds: LookupMap::new(StorageKey::TokenRefunds),
+            #[cfg(feature = "ft")]
+            ft: FungibleToken::new(),
+            #[cfg(feature = "nft")]
+            nft,
+            #[cfg(feature = "mt")]
+            mt: MultiToken::new(),
+        }
+    }
+
+    /// Replaces upgrade delay with the pending one, if it has taken effect
+    fn apply_pending_upgrade_delay(&mut self) {
+        self.upgrade_delay = self.upgrade_delay().0;
+
+        if self.pending_upgrade_delay().is_none() {
+            self.pending_upgrade_delay = None;
+        }
+    }
+}
+
+impl Hash for Vec<u8> {
+    fn hash(&self) -> Vec<u8> {
+        env::sha256(self)
+    }
+}
+
+/// Deploys `code` to the current account and calls `migrate` on it in the same batch
+///
+/// If migration fails, the whole batch is reverted and the current code remains deployed
+pub(crate) fn deploy_and_migrate(code: Vec<u8>) -> Promise {
+    let gas = env::prepaid_gas()
+        .0
+        .checked_sub(env::used_gas().0 + GAS_FOR_UPGRADE.0)
+        .unwrap_or_else(|| env::panic_str("Not enough gas to upgrade"));
+
+    Promise::new(env::current_account_id())
+        .deploy_contract(code)
+        .function_call("migrate".to_owned(), vec![], 0, gas.into())
+}
+
+/// Returns version and token features of the stored contract state, `None` if the state predates versioning
+fn read_state_version() -> Option<(u8, u8)> {
+    env::storage_read(&state_version_key()).map(|value| match value[..] {
+        [version, features] => (version, features),
+        _ => env::panic_str("Unsupported state version"),
+    })
+}
+
+/// Writes `STATE_VERSION` and `STATE_FEATURES` of the current contract state layout
+pub(crate) fn write_state_version() {
+    env::storage_write(&state_version_key(), &[STATE_VERSION, STATE_FEATURES]);
+}
+
+fn state_version_key() -> Vec<u8> {
+    StorageKey::StateVersion.into_storage_key()
+}
+
+/// Stages new contract code provided as raw input of the `upgrade` entrypoint, same as `stage_code`
+///
+/// Staged code is deployed only by `deploy_staged_code` after `upgrade_delay`
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub(crate) fn stage_code_from_input() {
+    let mut contract: Contract =
+        env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"));
+
+    let code = env::input().unwrap_or_else(|| env::panic_str("Contract code is not provided"));
+    contract.stage_code(code);
+
+    env::state_write(&contract);
+}
+
+#[cfg(target_arch = "wasm32")]
+mod entrypoint {
+    use near_sdk::env;
+
+    /// Stages new contract code provided as raw input, see `stage_code`
+    ///
+    /// Available to the owner and upgraders with 1 yoctoNEAR attached
+    #[no_mangle]
+    pub extern "C" fn upgrade() {
+        env::setup_panic_hook();
+
+        super::stage_code_from_input();
+    }
+}
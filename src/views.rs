@@ -0,0 +1,91 @@
+use crate::indexed_set::IndexedSet;
+use crate::{Contract, ContractExt};
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, require, AccountId};
+
+/// Maximum number of elements returned by paginated views, bounds gas used by a single view call
+pub(crate) const MAX_PAGE_SIZE: u64 = 100;
+
+/// Registered account id with its storage balance
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountStorageBalance {
+    pub account_id: AccountId,
+    pub storage_balance: StorageBalance,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns number of indexed registered accounts
+    ///
+    /// Accounts registered before the index was introduced are counted after upgrade of their data
+    /// or after they are indexed by `index_legacy_accounts`
+    pub fn accounts_count(&self) -> u32 {
+        self.account_ids.len()
+    }
+
+    /// Returns paginated list of registered accounts with their storage balances
+    ///
+    /// Accounts registered before the index was introduced are listed after upgrade of their data
+    /// or after they are indexed by `index_legacy_accounts`
+    ///
+    /// - `from_index`: index of the first account to return, defaults to 0
+    /// - `limit`: max number of accounts to return, defaults to and is capped by `MAX_PAGE_SIZE`
+    pub fn get_accounts(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountStorageBalance> {
+        paginate(&self.account_ids, from_index, limit)
+            .map(|account_id| AccountStorageBalance {
+                account_id: account_id.clone(),
+                storage_balance: self
+                    .get_account(account_id)
+                    .map(|account| account.storage_balance())
+                    .unwrap_or_else(|e| env::panic_str(e)),
+            })
+            .collect()
+    }
+
+    /// Returns sponsor of the registered `account_id`, if any
+    pub fn sponsor_of(&self, account_id: AccountId) -> Option<AccountId> {
+        self.get_account(&account_id)
+            .ok()
+            .and_then(|account| account.sponsor_id)
+    }
+
+    /// Returns whether the registered `account_id` is frozen
+    pub fn is_frozen(&self, account_id: AccountId) -> bool {
+        self.get_account(&account_id)
+            .map(|account| account.frozen)
+            .unwrap_or_default()
+    }
+}
+
+/// Returns iterator over page of `set` elements
+///
+/// - `from_index`: index of the first element to return, defaults to 0
+/// - `limit`: max number of elements to return, defaults to and is capped by `MAX_PAGE_SIZE`
+pub(crate) fn paginate<T>(
+    set: &IndexedSet<T>,
+    from_index: Option<U128>,
+    limit: Option<u64>,
+) -> impl Iterator<Item = &T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+    require!(
+        (set.len() as u128) >= start_index,
+        "Out of bounds, please use a smaller from_index."
+    );
+
+    let limit = limit.unwrap_or(MAX_PAGE_SIZE);
+    require!(limit != 0, "Cannot provide limit of 0.");
+
+    set.iter_from(start_index as u32)
+        .take(limit.min(MAX_PAGE_SIZE) as usize)
+}